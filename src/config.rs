@@ -0,0 +1,418 @@
+//! Custom games and detector settings loaded from a TOML or JSON file.
+//!
+//! ```toml
+//! [detectors]
+//! # Detectors listed here run first, in this order. "custom" is the detector for `games`.
+//! order = ["custom", "steam"]
+//! disabled = ["minecraft"]
+//!
+//! [[games]]
+//! id = "celeste"
+//! # Metadata returned by `DetectedGame::get_info`. Every field is optional.
+//! name = "Celeste"
+//! cover = "https://example.com/celeste.png"
+//! developers = ["Maddy Makes Games"]
+//! app_id = 504230
+//! # Match conditions, as in `ProcessMatcher`. At least one is required and all of them have to hold.
+//! exe_name = "Celeste"
+//! exe_path = "/opt/games/**/Celeste"
+//! cmdline = "(?i)celeste"
+//! cwd = "/opt/games/*"
+//! env = { SDL_VIDEODRIVER = "wayland" }
+//! parent = { exe_name = "steam" }
+//! # Processes matching any of these are skipped.
+//! exclude = [{ exe_name = "vim" }]
+//! ```
+//!
+//! The JSON format has the same structure:
+//!
+//! ```json
+//! {
+//!     "detectors": { "order": ["custom"] },
+//!     "games": [{ "id": "celeste", "name": "Celeste", "exe_name": "Celeste" }]
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::read_to_string;
+use std::path::Path;
+
+use glob::Pattern;
+use regex::Regex;
+use serde::de::Visitor;
+use serde::{Deserialize, Deserializer};
+
+use crate::platforms::custom::{CustomDetector, CustomGameInfo, CustomGameRule, ProcessMatcher};
+use crate::{ConfigError, DetectorRegistry};
+
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    pub games: Vec<CustomGameRule>,
+    pub detectors: DetectorSettings,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DetectorSettings {
+    /// Detector names to run first, in order. See [`DetectorRegistry::set_order`].
+    pub order: Vec<String>,
+    /// Detector names to leave out of the registry.
+    pub disabled: Vec<String>,
+}
+
+impl Config {
+    /// Reads a config file, picking the format from its `.toml` or `.json` extension.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let source = read_to_string(path).map_err(|source| ConfigError::Io { path: path.to_path_buf(), source })?;
+        let config = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Self::from_toml(&source),
+            Some("json") => Self::from_json(&source),
+            _ => return Err(ConfigError::UnknownFormat { path: path.to_path_buf() }),
+        };
+        config.map_err(|error| match error {
+            ConfigError::Invalid { line, column, message, .. } => ConfigError::Invalid { path: Some(path.to_path_buf()), line, column, message },
+            error => error,
+        })
+    }
+
+    pub fn from_toml(source: &str) -> Result<Self, ConfigError> {
+        let raw: RawConfig = toml::from_str(source).map_err(|error| {
+            let (line, column) = error.span().map_or((1, 1), |span| line_and_column(source, span.start));
+            ConfigError::Invalid { path: None, line, column, message: error.message().to_owned() }
+        })?;
+        Ok(raw.into())
+    }
+
+    pub fn from_json(source: &str) -> Result<Self, ConfigError> {
+        let raw: RawConfig = serde_json::from_str(source).map_err(|error| {
+            let message = error.to_string();
+            // serde_json appends the location to every message.
+            let suffix = format!(" at line {} column {}", error.line(), error.column());
+            ConfigError::Invalid {
+                path: None,
+                line: error.line(),
+                column: error.column(),
+                message: message.strip_suffix(&suffix).unwrap_or(&message).to_owned(),
+            }
+        })?;
+        Ok(raw.into())
+    }
+
+    /// Builds a registry with the built-in detectors and the configured games, ordered and
+    /// filtered by the detector settings.
+    pub fn registry(&self) -> DetectorRegistry {
+        let mut registry = DetectorRegistry::new();
+        if !self.games.is_empty() {
+            registry.insert(0, CustomDetector::with_rules(self.games.clone()));
+        }
+        for name in &self.detectors.disabled {
+            registry.remove(name);
+        }
+        let order: Vec<&str> = self.detectors.order.iter().map(String::as_str).collect();
+        registry.set_order(&order);
+        registry
+    }
+}
+
+// Both line and column start at 1, like serde_json's.
+fn line_and_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |line| line.chars().count()) + 1;
+    (line, column)
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawConfig {
+    #[serde(default)]
+    games: Vec<RawGame>,
+    #[serde(default)]
+    detectors: DetectorSettings,
+}
+
+impl From<RawConfig> for Config {
+    fn from(raw: RawConfig) -> Self {
+        Config {
+            games: raw.games.into_iter().map(|game| game.0).collect(),
+            detectors: raw.detectors,
+        }
+    }
+}
+
+struct RawGame(CustomGameRule);
+
+impl<'de> Deserialize<'de> for RawGame {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Game {
+            id: String,
+            #[serde(default)]
+            name: String,
+            #[serde(default)]
+            description: String,
+            #[serde(default)]
+            cover: String,
+            #[serde(default)]
+            icon: String,
+            #[serde(default)]
+            developers: Vec<String>,
+            #[serde(default)]
+            publishers: Vec<String>,
+            #[serde(default)]
+            url: String,
+            #[serde(default)]
+            app_id: Option<i64>,
+            #[serde(default)]
+            exe_name: Option<String>,
+            #[serde(default)]
+            exe_path: Option<GlobValue>,
+            #[serde(default)]
+            cmdline: Option<RegexValue>,
+            #[serde(default)]
+            cwd: Option<GlobValue>,
+            #[serde(default)]
+            env: BTreeMap<String, String>,
+            #[serde(default)]
+            parent: Option<RawMatcher>,
+            #[serde(default)]
+            exclude: Vec<RawMatcher>,
+        }
+
+        let game = Game::deserialize(deserializer)?;
+        let matcher = RawMatcher {
+            exe_name: game.exe_name,
+            exe_path: game.exe_path,
+            cmdline: game.cmdline,
+            cwd: game.cwd,
+            env: game.env,
+            parent: game.parent.map(Box::new),
+        }.into_matcher();
+        if matcher.is_empty() {
+            return Err(serde::de::Error::custom(format!(
+                "game `{}` needs at least one of exe_name, exe_path, cmdline, cwd, env or parent", game.id
+            )));
+        }
+        let info = CustomGameInfo {
+            name: game.name,
+            description: game.description,
+            cover: game.cover,
+            icon: game.icon,
+            developers: game.developers,
+            publishers: game.publishers,
+            url: game.url,
+            app_id: game.app_id,
+        };
+        let mut rule = CustomGameRule::new(game.id, matcher).with_info(info);
+        rule.exclude = game.exclude.into_iter().map(RawMatcher::into_matcher).collect();
+        Ok(RawGame(rule))
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RawMatcher {
+    exe_name: Option<String>,
+    exe_path: Option<GlobValue>,
+    cmdline: Option<RegexValue>,
+    cwd: Option<GlobValue>,
+    env: BTreeMap<String, String>,
+    parent: Option<Box<RawMatcher>>,
+}
+
+impl RawMatcher {
+    fn into_matcher(self) -> ProcessMatcher {
+        ProcessMatcher {
+            exe_name: self.exe_name,
+            exe_path: self.exe_path.map(|glob| glob.0),
+            cmdline: self.cmdline.map(|regex| regex.0),
+            cmdline_contains: None,
+            cwd: self.cwd.map(|glob| glob.0),
+            env: self.env,
+            parent: self.parent.map(|parent| Box::new(parent.into_matcher())),
+        }
+    }
+}
+
+// Patterns are compiled inside the visitor so that errors point at the offending string.
+struct PatternVisitor<T>(&'static str, fn(&str) -> Result<T, String>);
+
+impl<T> Visitor<'_> for PatternVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.0)
+    }
+
+    fn visit_str<E: serde::de::Error>(self, pattern: &str) -> Result<T, E> {
+        (self.1)(pattern).map_err(E::custom)
+    }
+}
+
+struct GlobValue(Pattern);
+
+impl<'de> Deserialize<'de> for GlobValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(PatternVisitor("a glob pattern", |pattern| {
+            Pattern::new(pattern).map(GlobValue).map_err(|error| format!("invalid glob `{pattern}`: {error}"))
+        }))
+    }
+}
+
+struct RegexValue(Regex);
+
+impl<'de> Deserialize<'de> for RegexValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(PatternVisitor("a regular expression", |pattern| {
+            Regex::new(pattern).map(RegexValue).map_err(|error| format!("invalid regex `{pattern}`: {error}"))
+        }))
+    }
+}
+
+/// Keeps a [`Config`] in sync with its file. Reloading stops when the watcher is dropped.
+#[cfg(feature = "config-watch")]
+pub struct ConfigWatcher {
+    config: std::sync::Arc<std::sync::Mutex<Config>>,
+    _watcher: notify::RecommendedWatcher,
+}
+
+#[cfg(feature = "config-watch")]
+impl ConfigWatcher {
+    /// Loads `path` and reloads it whenever it changes, calling `on_reload` with the outcome. An
+    /// invalid file is reported and the previous config is kept.
+    pub fn new<F>(path: impl Into<std::path::PathBuf>, on_reload: F) -> Result<Self, ConfigError>
+    where
+        F: Fn(Result<&Config, &ConfigError>) + Send + 'static,
+    {
+        use notify::{EventKind, RecursiveMode, Watcher};
+        use std::sync::{Arc, Mutex};
+
+        let path = path.into();
+        let config = Arc::new(Mutex::new(Config::load(&path)?));
+        let shared = config.clone();
+        let watched = path.clone();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) || !event.paths.iter().any(|path| path.file_name() == watched.file_name()) {
+                return;
+            }
+            match Config::load(&watched) {
+                Ok(config) => {
+                    *shared.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = config.clone();
+                    on_reload(Ok(&config));
+                }
+                Err(error) => on_reload(Err(&error)),
+            }
+        })?;
+        // Editors often replace the file instead of writing to it, so the directory is watched.
+        let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        Ok(Self { config, _watcher: watcher })
+    }
+
+    /// The most recent valid config.
+    pub fn config(&self) -> Config {
+        self.config.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOML: &str = r#"
+[detectors]
+order = ["custom", "steam"]
+disabled = ["minecraft"]
+
+[[games]]
+id = "celeste"
+name = "Celeste"
+app_id = 504230
+exe_name = "Celeste"
+cwd = "/opt/games/*"
+env = { SDL_VIDEODRIVER = "wayland" }
+parent = { exe_name = "steam" }
+exclude = [{ cmdline = "(?i)vim" }]
+"#;
+
+    #[test]
+    fn test_load() {
+        let config = Config::from_toml(TOML).unwrap();
+        assert_eq!(config.detectors, DetectorSettings {
+            order: vec!["custom".to_owned(), "steam".to_owned()],
+            disabled: vec!["minecraft".to_owned()],
+        });
+        let game = &config.games[0];
+        assert_eq!(game.id, "celeste");
+        assert_eq!(game.info.name, "Celeste");
+        assert_eq!(game.info.app_id, Some(504230));
+        assert_eq!(game.matcher.exe_name.as_deref(), Some("Celeste"));
+        assert_eq!(game.matcher.parent.as_ref().unwrap().exe_name.as_deref(), Some("steam"));
+        assert_eq!(game.exclude.len(), 1);
+
+        let names = config.registry().names().into_iter().map(str::to_owned).collect::<Vec<_>>();
+        assert_eq!(names[0], "custom");
+        assert!(!names.contains(&"minecraft".to_owned()));
+
+        let json = r#"{ "detectors": { "order": ["custom"] }, "games": [{ "id": "celeste", "exe_name": "Celeste" }] }"#;
+        let config = Config::from_json(json).unwrap();
+        assert_eq!(config.games[0].id, "celeste");
+        assert_eq!(config.detectors.order, vec!["custom"]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = Config::from_toml("[[games]]\nid = \"a\"\nexe_name = \"a\"\ncmdline = \"(\"\n").unwrap_err();
+        assert!(matches!(error, ConfigError::Invalid { line: 4, .. }), "{error}");
+        assert!(error.to_string().starts_with("line 4, column 11: invalid regex `(`"), "{error}");
+
+        let error = Config::from_toml("[[games]]\nid = \"a\"\nexe_nam = \"a\"\n").unwrap_err();
+        assert!(matches!(error, ConfigError::Invalid { line: 3, .. }), "{error}");
+
+        let error = Config::from_json("{\n  \"games\": [{\n    \"id\": \"a\", \"exe_path\": \"[\"\n  }]\n}").unwrap_err();
+        assert!(matches!(error, ConfigError::Invalid { line: 3, .. }), "{error}");
+        assert!(error.to_string().contains("invalid glob `[`"), "{error}");
+
+        let error = Config::from_json("{ \"games\": [{ \"id\": \"a\" }] }").unwrap_err();
+        assert!(error.to_string().contains("game `a` needs at least one of"), "{error}");
+    }
+
+    #[test]
+    #[cfg(feature = "config-watch")]
+    fn test_watch() {
+        use std::sync::mpsc::channel;
+        use std::time::Duration;
+
+        let dir = std::env::temp_dir().join(format!("game-detect-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("games.toml");
+        std::fs::write(&path, TOML).unwrap();
+
+        let (sender, receiver) = channel();
+        let watcher = ConfigWatcher::new(&path, move |config| {
+            let _ = sender.send(config.map(|config| config.games.len()).map_err(|error| error.to_string()));
+        }).unwrap();
+        assert_eq!(watcher.config().games.len(), 1);
+
+        // Replaced in one step so the watcher never sees a half-written file.
+        let replace = |contents: &str| {
+            std::fs::write(dir.join("games.toml.tmp"), contents).unwrap();
+            std::fs::rename(dir.join("games.toml.tmp"), &path).unwrap();
+        };
+
+        replace("[[games]]\nid = \"a\"\n");
+        assert!(receiver.recv_timeout(Duration::from_secs(5)).unwrap().is_err());
+        assert_eq!(watcher.config().games.len(), 1);
+
+        replace("");
+        while receiver.recv_timeout(Duration::from_secs(5)).unwrap() != Ok(0) {}
+        assert!(watcher.config().games.is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
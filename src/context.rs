@@ -0,0 +1,167 @@
+use std::path::{Component, Path, PathBuf};
+
+use procfs::{FromReadSI, KernelStats};
+
+use crate::{DetectError, ProcessInfo, ProcessSnapshot};
+
+/// Where detection reads the system from. The defaults match the machine the code runs on.
+///
+/// When running in a container with the host's `/proc` mounted at `/host/proc` and the host
+/// filesystem at `/host`, set `proc_root` to `/host/proc`, `root` to `/host` and `home` to the
+/// user's home directory as the host sees it, such as `/home/alice`. If only the home directory is
+/// mounted, set `home` to the mount point instead and leave `root` unset.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct DetectOptions {
+    /// The procfs mount to list processes from. Defaults to `/proc`.
+    pub proc_root: Option<PathBuf>,
+    /// The home directory launcher data is found under. Defaults to the current user's.
+    pub home: Option<PathBuf>,
+    /// The directory every absolute path, including `home`, is resolved below.
+    pub root: Option<PathBuf>,
+    /// Steam installs to search before the standard locations, such as a portable install on
+    /// another drive. Present without the `steam` feature too, so that enabling it elsewhere in a
+    /// build doesn't break struct literals; it is ignored then.
+    pub steam_roots: Vec<PathBuf>,
+}
+
+/// The view of the system detectors work against: the running processes, the user's home
+/// directory and where files can be read from.
+///
+/// Paths found in process command lines and launcher data are the paths the game sees. When the
+/// context has a `root`, such as the files directory of a snapshot, those paths are read from
+/// below it instead. Use [`DetectContext::path`] before touching the filesystem.
+#[derive(Debug, Clone, Default)]
+pub struct DetectContext {
+    processes: ProcessSnapshot,
+    home: Option<PathBuf>,
+    root: Option<PathBuf>,
+    steam_roots: Vec<PathBuf>,
+}
+
+impl DetectContext {
+    pub fn new(processes: Vec<ProcessInfo>, home: Option<PathBuf>) -> Self {
+        Self { processes: ProcessSnapshot::new(processes), home, ..Default::default() }
+    }
+
+    /// Reads the running processes from `/proc` and uses the current user's home directory.
+    pub fn live() -> Result<Self, DetectError> {
+        Self::with_options(&DetectOptions::default())
+    }
+
+    /// Reads the running processes and finds launcher data where `options` points.
+    pub fn with_options(options: &DetectOptions) -> Result<Self, DetectError> {
+        let processes = match &options.proc_root {
+            Some(proc_root) => {
+                // Start times are relative to the boot time of the machine that owns the procfs.
+                let boot_time = KernelStats::from_file(proc_root.join("stat"), procfs::current_system_info())?.btime;
+                procfs::process::all_processes_with_root(proc_root)?
+                    .filter_map(|process| process.ok())
+                    .map(|process| ProcessInfo::read(&process, Some(boot_time)))
+                    .collect()
+            }
+            None => procfs::process::all_processes()?
+                .filter_map(|process| process.ok())
+                .map(|process| ProcessInfo::from_process(&process))
+                .collect(),
+        };
+        Ok(Self { processes: ProcessSnapshot::new(processes), ..Self::without_processes(options) })
+    }
+
+    /// Finds launcher data where `options` points without listing processes, for lookups that
+    /// only read files.
+    pub fn without_processes(options: &DetectOptions) -> Self {
+        let context = Self::new(vec![], options.home.clone().or_else(home::home_dir));
+        let context = context.with_steam_roots(options.steam_roots.clone());
+        match &options.root {
+            Some(root) => context.with_root(root),
+            None => context,
+        }
+    }
+
+    pub fn with_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.root = Some(root.into());
+        self
+    }
+
+    pub fn with_steam_roots(mut self, steam_roots: Vec<PathBuf>) -> Self {
+        self.steam_roots = steam_roots;
+        self
+    }
+
+    pub fn steam_roots(&self) -> &[PathBuf] {
+        &self.steam_roots
+    }
+
+    pub fn processes(&self) -> &ProcessSnapshot {
+        &self.processes
+    }
+
+    pub fn home(&self) -> Result<&Path, DetectError> {
+        self.home.as_deref().ok_or(DetectError::NoHomeDir)
+    }
+
+    /// Where per-user application data lives: `~/.config` on Linux, `~/Library/Application Support`
+    /// on macOS and `%LOCALAPPDATA%` on Windows.
+    pub fn app_data_dir(&self) -> Result<PathBuf, DetectError> {
+        let home = self.home()?;
+        #[cfg(target_os = "macos")]
+        return Ok(home.join("Library").join("Application Support"));
+        #[cfg(target_family = "windows")]
+        return Ok(home.join("AppData").join("Local"));
+        #[cfg(not(any(target_os = "macos", target_family = "windows")))]
+        return Ok(home.join(".config"));
+    }
+
+    /// Maps a path as seen by the detected processes to the path it can be read from.
+    pub fn path(&self, path: impl AsRef<Path>) -> PathBuf {
+        match &self.root {
+            Some(root) => root.join(relative(path.as_ref())),
+            None => path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+// Drops the root and any drive prefix so the path can be joined below another directory.
+pub(crate) fn relative(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|component| !matches!(component, Component::Prefix(_) | Component::RootDir))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_options() {
+        let options = DetectOptions {
+            proc_root: Some(PathBuf::from("/proc")),
+            home: Some(PathBuf::from("/home/alice")),
+            root: Some(PathBuf::from("/host")),
+            ..Default::default()
+        };
+        let context = DetectContext::with_options(&options).unwrap();
+        let pid = std::process::id() as i32;
+        let myself = context.processes().get(pid).unwrap().info;
+        assert_eq!(myself.exe, std::env::current_exe().ok());
+        assert!(myself.start_time.is_some());
+        assert_eq!(context.home().unwrap(), Path::new("/home/alice"));
+        assert_eq!(context.path("/home/alice/.steam/registry.vdf"), PathBuf::from("/host/home/alice/.steam/registry.vdf"));
+
+        let context = DetectContext::without_processes(&DetectOptions { steam_roots: vec![PathBuf::from("/games/Steam")], ..options });
+        assert!(context.processes().infos().is_empty());
+        assert_eq!(context.home().unwrap(), Path::new("/home/alice"));
+        assert_eq!(context.steam_roots(), [PathBuf::from("/games/Steam")]);
+        assert_eq!(context.path("/games/Steam"), PathBuf::from("/host/games/Steam"));
+    }
+
+    #[test]
+    fn test_path() {
+        let context = DetectContext::new(vec![], Some(PathBuf::from("/home/user")));
+        assert_eq!(context.path("/home/user/.steam/registry.vdf"), PathBuf::from("/home/user/.steam/registry.vdf"));
+
+        let context = context.with_root("/tmp/snapshot/files");
+        assert_eq!(context.home().unwrap(), Path::new("/home/user"));
+        assert_eq!(context.path("/home/user/.steam/registry.vdf"), PathBuf::from("/tmp/snapshot/files/home/user/.steam/registry.vdf"));
+    }
+}
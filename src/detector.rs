@@ -0,0 +1,320 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+#[cfg(feature = "http")]
+use std::{future::Future, pin::Pin};
+
+use crate::{DetectContext, DetectError, DetectedGame, Detection};
+#[cfg(test)]
+use crate::ProcessInfo;
+#[cfg(feature = "http")]
+use crate::{FetchError, GameInfo, InfoOptions};
+
+#[cfg(feature = "http")]
+pub type InfoFuture<'a> = Pin<Box<dyn Future<Output = Result<GameInfo, FetchError>> + Send + 'a>>;
+
+/// A source of running games, such as a launcher or a store client.
+pub trait Detector: Send + Sync {
+    /// A short, unique name used to find and reorder the detector in a [`DetectorRegistry`].
+    fn name(&self) -> &str;
+
+    /// Returns every game this detector can see, one per process or session.
+    fn detect(&self, context: &DetectContext) -> Result<Vec<Detection>, DetectError>;
+
+    /// The launcher files `detect` reads, as seen from `context`. Snapshots copy these so detection
+    /// can be replayed later.
+    fn data_files(&self, _context: &DetectContext) -> Vec<PathBuf> {
+        vec![]
+    }
+
+    /// Whether `detect` reads the environment variable `key`. Snapshots keep only the variables some
+    /// detector reads, as the rest can hold secrets.
+    fn reads_environ(&self, _key: &str) -> bool {
+        false
+    }
+
+    /// Looks up metadata for a game this detector produced. Returning `None` falls back to
+    /// [`DetectedGame::get_info`].
+    #[cfg(feature = "http")]
+    fn fetch_info<'a>(&'a self, _game: &'a DetectedGame) -> Option<InfoFuture<'a>> {
+        None
+    }
+
+    /// Like `fetch_info`, for detectors that can localize. Defaults to `fetch_info`.
+    #[cfg(feature = "http")]
+    fn fetch_info_with<'a>(&'a self, game: &'a DetectedGame, _options: &'a InfoOptions) -> Option<InfoFuture<'a>> {
+        self.fetch_info(game)
+    }
+}
+
+/// Everything [`DetectorRegistry::detect_all_report`] found, and the detectors that failed.
+#[derive(Debug, Default)]
+pub struct DetectReport {
+    pub detections: Vec<Detection>,
+    /// The name and error of each detector that failed. Their games are missing from `detections`.
+    pub errors: Vec<(String, DetectError)>,
+    // The name of the detector behind each detection, so the watcher can tell whose games went
+    // missing because it failed.
+    pub(crate) detectors: Vec<String>,
+}
+
+/// An ordered list of detectors. Earlier detectors take priority over later ones.
+pub struct DetectorRegistry {
+    detectors: Vec<Box<dyn Detector>>,
+}
+
+impl DetectorRegistry {
+    /// Creates a registry containing every built-in detector enabled by cargo features.
+    pub fn new() -> Self {
+        #[allow(unused_mut)]
+        let mut registry = Self::empty();
+
+        #[cfg(feature = "steam")]
+        registry.register(crate::platforms::steam::SteamDetector::new());
+        #[cfg(feature = "itchio")]
+        registry.register(crate::platforms::itchio::ItchIoDetector);
+        #[cfg(feature = "gamejolt")]
+        registry.register(crate::platforms::gamejolt::GameJoltDetector);
+        #[cfg(all(feature = "lutris", target_os = "linux"))]
+        registry.register(crate::platforms::lutris::LutrisDetector);
+        #[cfg(feature = "roblox")]
+        registry.register(crate::platforms::roblox::RobloxDetector);
+        #[cfg(feature = "minecraft")]
+        registry.register(crate::platforms::minecraft::MinecraftDetector);
+
+        registry
+    }
+
+    pub fn empty() -> Self {
+        Self { detectors: vec![] }
+    }
+
+    /// Adds a detector at the end of the list, replacing any detector with the same name.
+    pub fn register<D: Detector + 'static>(&mut self, detector: D) {
+        match self.position(detector.name()) {
+            Some(index) => self.detectors[index] = Box::new(detector),
+            None => self.detectors.push(Box::new(detector)),
+        }
+    }
+
+    /// Adds a detector at `index`, replacing any detector with the same name.
+    pub fn insert<D: Detector + 'static>(&mut self, index: usize, detector: D) {
+        self.remove(detector.name());
+        let index = index.min(self.detectors.len());
+        self.detectors.insert(index, Box::new(detector));
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Box<dyn Detector>> {
+        self.position(name).map(|index| self.detectors.remove(index))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Detector> {
+        self.position(name).map(|index| self.detectors[index].as_ref())
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.detectors.iter().map(|detector| detector.name()).collect()
+    }
+
+    /// Moves the named detector to `index`. Returns `false` if no such detector is registered.
+    pub fn move_to(&mut self, name: &str, index: usize) -> bool {
+        match self.remove(name) {
+            Some(detector) => {
+                let index = index.min(self.detectors.len());
+                self.detectors.insert(index, detector);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Puts the named detectors first, in the given order. Unknown names are ignored and
+    /// detectors that aren't named keep their relative order after the named ones.
+    pub fn set_order(&mut self, names: &[&str]) {
+        let mut index = 0;
+        for name in names {
+            if self.move_to(name, index) {
+                index += 1;
+            }
+        }
+    }
+
+    pub fn detect(&self) -> Result<Option<DetectedGame>, DetectError> {
+        self.detect_with(&DetectContext::live()?)
+    }
+
+    /// The first game found. A detector that fails is skipped, and its error only returned if no
+    /// other detector finds a game.
+    pub fn detect_with(&self, context: &DetectContext) -> Result<Option<DetectedGame>, DetectError> {
+        let mut first_error = None;
+        for detector in &self.detectors {
+            match detector.detect(context) {
+                Ok(detections) => if let Some(detection) = detections.into_iter().next() {
+                    return Ok(Some(detection.game));
+                },
+                Err(error) => {
+                    first_error.get_or_insert(error);
+                }
+            }
+        }
+        first_error.map_or(Ok(None), Err)
+    }
+
+    pub fn detect_all(&self) -> Result<Vec<Detection>, DetectError> {
+        self.detect_all_with(&DetectContext::live()?)
+    }
+
+    /// Runs every detector and returns all of their detections. A process claimed by an earlier
+    /// detector is not reported again by later ones. Detectors that fail are skipped; use
+    /// [`DetectorRegistry::detect_all_report`] to see why.
+    pub fn detect_all_with(&self, context: &DetectContext) -> Result<Vec<Detection>, DetectError> {
+        Ok(self.detect_all_report(context).detections)
+    }
+
+    /// Like [`DetectorRegistry::detect_all_with`], together with the errors of the detectors that
+    /// failed.
+    pub fn detect_all_report(&self, context: &DetectContext) -> DetectReport {
+        let mut claimed = HashSet::new();
+        let mut report = DetectReport::default();
+        for detector in &self.detectors {
+            let detections = match detector.detect(context) {
+                Ok(detections) => detections,
+                Err(error) => {
+                    report.errors.push((detector.name().to_owned(), error));
+                    continue;
+                }
+            };
+            for detection in detections {
+                if let Some(pid) = detection.pid() {
+                    if !claimed.insert(pid) {
+                        continue;
+                    }
+                }
+                if !report.detections.contains(&detection) {
+                    report.detections.push(detection);
+                    report.detectors.push(detector.name().to_owned());
+                }
+            }
+        }
+        report
+    }
+
+    /// Every launcher file the registered detectors read, as seen from `context`.
+    pub fn data_files(&self, context: &DetectContext) -> Vec<PathBuf> {
+        self.detectors.iter().flat_map(|detector| detector.data_files(context)).collect()
+    }
+
+    /// Whether any registered detector reads the environment variable `key`.
+    pub fn reads_environ(&self, key: &str) -> bool {
+        self.detectors.iter().any(|detector| detector.reads_environ(key))
+    }
+
+    #[cfg(feature = "http")]
+    pub async fn get_info(&self, game: &DetectedGame) -> Result<GameInfo, FetchError> {
+        self.get_info_with(game, &InfoOptions::default()).await
+    }
+
+    #[cfg(feature = "http")]
+    pub async fn get_info_with(&self, game: &DetectedGame, options: &InfoOptions) -> Result<GameInfo, FetchError> {
+        for detector in &self.detectors {
+            if let Some(info) = detector.fetch_info_with(game, options) {
+                return info.await;
+            }
+        }
+        game.get_info_with(options).await
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.detectors.iter().position(|detector| detector.name() == name)
+    }
+}
+
+impl Default for DetectorRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom(id: &str, pid: Option<i32>) -> Detection {
+        Detection::new(DetectedGame::custom(id.to_owned()), pid.map(|pid| ProcessInfo { pid, ..Default::default() }))
+    }
+
+    struct Fixed(&'static str, Vec<(&'static str, Option<i32>)>);
+
+    impl Detector for Fixed {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn detect(&self, _context: &DetectContext) -> Result<Vec<Detection>, DetectError> {
+            Ok(self.1.iter().map(|(id, pid)| custom(id, *pid)).collect())
+        }
+    }
+
+    struct Failing;
+
+    impl Detector for Failing {
+        fn name(&self) -> &str {
+            "failing"
+        }
+
+        fn detect(&self, _context: &DetectContext) -> Result<Vec<Detection>, DetectError> {
+            Err(DetectError::parse("receipt.json.gz", "unexpected end of file"))
+        }
+    }
+
+    #[test]
+    fn test_registry_order() {
+        let mut registry = DetectorRegistry::empty();
+        registry.register(Fixed("a", vec![]));
+        registry.register(Fixed("b", vec![("b", None)]));
+        registry.register(Fixed("c", vec![("c", None)]));
+        assert_eq!(registry.detect_with(&DetectContext::default()).unwrap(), Some(DetectedGame::custom("b")));
+
+        registry.set_order(&["c", "missing", "a"]);
+        assert_eq!(registry.names(), vec!["c", "a", "b"]);
+        assert_eq!(registry.detect_with(&DetectContext::default()).unwrap(), Some(DetectedGame::custom("c")));
+
+        registry.register(Fixed("c", vec![]));
+        assert_eq!(registry.names(), vec!["c", "a", "b"]);
+        assert_eq!(registry.detect_with(&DetectContext::default()).unwrap(), Some(DetectedGame::custom("b")));
+
+        assert!(registry.remove("b").is_some());
+        assert!(!registry.move_to("b", 0));
+        assert_eq!(registry.detect_with(&DetectContext::default()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_detect_all() {
+        let mut registry = DetectorRegistry::empty();
+        registry.register(Fixed("a", vec![("a", Some(1)), ("a", Some(2))]));
+        registry.register(Fixed("b", vec![("b", Some(2)), ("b", Some(3)), ("b", None)]));
+        registry.register(Fixed("c", vec![("b", None)]));
+        let detections = registry.detect_all_with(&DetectContext::default()).unwrap();
+        assert_eq!(detections, vec![
+            custom("a", Some(1)),
+            custom("a", Some(2)),
+            custom("b", Some(3)),
+            custom("b", None),
+        ]);
+    }
+
+    #[test]
+    fn test_failing_detector() {
+        let mut registry = DetectorRegistry::empty();
+        registry.register(Failing);
+        registry.register(Fixed("a", vec![("a", Some(1))]));
+        let report = registry.detect_all_report(&DetectContext::default());
+        assert_eq!(report.detections, vec![custom("a", Some(1))]);
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].0, "failing");
+        assert_eq!(registry.detect_all_with(&DetectContext::default()).unwrap(), vec![custom("a", Some(1))]);
+        assert_eq!(registry.detect_with(&DetectContext::default()).unwrap(), Some(DetectedGame::custom("a")));
+
+        registry.remove("a");
+        assert!(matches!(registry.detect_with(&DetectContext::default()), Err(DetectError::Parse { .. })));
+    }
+}
@@ -0,0 +1,98 @@
+use std::fmt::Display;
+use std::io;
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+#[cfg(feature = "http")]
+use crate::GamePlatform;
+
+/// Errors returned while looking for running games.
+#[derive(Debug, Error)]
+pub enum DetectError {
+    #[error("launcher data not found at {path}")]
+    MissingLauncher { path: PathBuf },
+    #[error("permission denied reading {path}")]
+    PermissionDenied { path: PathBuf },
+    #[error("failed to parse {path}: {message}")]
+    Parse { path: PathBuf, message: String },
+    #[error("failed to read {path}: {source}")]
+    Io { path: PathBuf, source: io::Error },
+    #[error("the home directory could not be determined")]
+    NoHomeDir,
+    #[error("failed to read the process list: {0}")]
+    Process(#[from] procfs::ProcError),
+    /// A detector failed while the others went on, as reported by [`crate::GameWatcher`].
+    #[error("the {name} detector failed: {source}")]
+    Detector { name: String, source: Box<DetectError> },
+}
+
+// Unused when every launcher feature is disabled.
+#[allow(dead_code)]
+impl DetectError {
+    pub(crate) fn io(path: impl Into<PathBuf>, error: io::Error) -> Self {
+        let path = path.into();
+        match error.kind() {
+            io::ErrorKind::NotFound => DetectError::MissingLauncher { path },
+            io::ErrorKind::PermissionDenied => DetectError::PermissionDenied { path },
+            _ => DetectError::Io { path, source: error },
+        }
+    }
+
+    pub(crate) fn parse(path: impl Into<PathBuf>, error: impl Display) -> Self {
+        DetectError::Parse { path: path.into(), message: error.to_string() }
+    }
+}
+
+/// Errors returned while fetching metadata for a detected game.
+#[cfg(feature = "http")]
+#[derive(Debug, Error)]
+pub enum FetchError {
+    #[error("request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("server responded with {0}")]
+    Status(reqwest::StatusCode),
+    #[error("failed to parse response: {0}")]
+    Parse(String),
+    #[error("game not found")]
+    NotFound,
+    #[error("this provider can't fetch info for {0:?} games")]
+    Unsupported(GamePlatform),
+}
+
+#[cfg(feature = "http")]
+impl From<serde_json::Error> for FetchError {
+    fn from(error: serde_json::Error) -> Self {
+        FetchError::Parse(error.to_string())
+    }
+}
+
+/// Errors returned while loading a config file.
+#[cfg(feature = "config")]
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("failed to read {path}: {source}")]
+    Io { path: PathBuf, source: io::Error },
+    #[error("{path} is neither a .toml nor a .json file")]
+    UnknownFormat { path: PathBuf },
+    #[error("{}line {line}, column {column}: {message}", path.as_ref().map(|path| format!("{}: ", path.display())).unwrap_or_default())]
+    Invalid { path: Option<PathBuf>, line: usize, column: usize, message: String },
+    #[cfg(feature = "config-watch")]
+    #[error("failed to watch the config file: {0}")]
+    Watch(#[from] notify::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_io_error_kinds() {
+        let error = DetectError::io("registry.vdf", io::Error::from(io::ErrorKind::NotFound));
+        assert!(matches!(error, DetectError::MissingLauncher { .. }));
+        let error = DetectError::io("registry.vdf", io::Error::from(io::ErrorKind::PermissionDenied));
+        assert!(matches!(error, DetectError::PermissionDenied { .. }));
+        let error = DetectError::io("registry.vdf", io::Error::from(io::ErrorKind::InvalidData));
+        assert!(matches!(error, DetectError::Io { .. }));
+    }
+}
@@ -1,10 +1,35 @@
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 
-use anyhow::Result;
+#[cfg(feature = "config")]
+pub mod config;
+mod context;
+mod detector;
+mod error;
+pub mod platforms;
+mod process;
+#[cfg(feature = "snapshot")]
+pub mod snapshot;
+#[cfg(test)]
+mod testing;
+mod watcher;
 
-mod platforms;
+pub use context::{DetectContext, DetectOptions};
+pub use detector::{DetectReport, Detector, DetectorRegistry};
+pub use error::DetectError;
+#[cfg(feature = "config")]
+pub use error::ConfigError;
+#[cfg(feature = "http")]
+pub use error::FetchError;
+pub use process::{ProcessEntry, ProcessInfo, ProcessSnapshot};
+pub use watcher::{GameEvent, GameWatcher};
+#[cfg(feature = "stream")]
+pub use watcher::GameEventStream;
+#[cfg(feature = "http")]
+pub use detector::InfoFuture;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GameInfo {
     pub app_id: Option<i64>, // A steam app id, if it can be mapped to one.
     pub via_platform: GamePlatform,
@@ -18,7 +43,22 @@ pub struct GameInfo {
     pub url: String,
 }
 
+/// Preferences for the metadata [`DetectedGame::get_info_with`] returns. Providers that can't
+/// localize ignore the language and country.
+#[cfg(feature = "http")]
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct InfoOptions {
+    /// A BCP 47 language tag such as `de` or `pt-BR`.
+    pub language: Option<String>,
+    /// An ISO 3166-1 alpha-2 country code such as `DE`, which sets the store region.
+    pub country: Option<String>,
+    /// Where launcher data such as Steam's cache and custom artwork is read from. Pass the
+    /// options the game was detected with; `proc_root` is unused.
+    pub detect_options: DetectOptions,
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
 pub enum GamePlatform {
     #[cfg(feature = "minecraft")]
     MinecraftLauncher,
@@ -35,13 +75,25 @@ pub enum GamePlatform {
     Custom,
 }
 
-#[derive(Debug, Clone, Eq, Hash)]
+/// Serialized with the variant name in a `platform` field and its fields under `game`, for example
+/// `{"platform":"steam","game":{"id":570,...}}`.
+#[derive(Debug, Clone, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(tag = "platform", content = "game", rename_all = "snake_case"))]
 pub enum DetectedGame {
     #[cfg(feature = "steam")]
     Steam {
         id: i64,
         url: String,
         icon: String,
+        /// Set when the game runs through Proton or another compatibility tool.
+        #[cfg_attr(feature = "serde", serde(default))]
+        compatibility: Option<Box<platforms::steam::CompatibilityInfo>>,
+    },
+    /// A non-Steam game added to the Steam library. `id` is the shortcut's app id.
+    #[cfg(feature = "steam")]
+    SteamShortcut {
+        id: i64,
+        shortcut: Box<platforms::steam::Shortcut>,
     },
     #[cfg(feature = "gamejolt")]
     GameJolt {
@@ -92,40 +144,96 @@ pub enum DetectedGame {
         cover: String,
         icon: String,
     },
-    Custom(String),
+    Custom {
+        id: String,
+        info: Box<platforms::custom::CustomGameInfo>,
+    },
+}
+
+/// A detected game together with the process it was found in. `process` is `None` for detections
+/// that don't come from a specific process, such as Steam's `RunningAppID`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Detection {
+    pub game: DetectedGame,
+    pub process: Option<ProcessInfo>,
+}
+
+impl Detection {
+    pub fn new(game: DetectedGame, process: Option<ProcessInfo>) -> Self {
+        Self { game, process }
+    }
+
+    pub fn pid(&self) -> Option<i32> {
+        self.process.as_ref().map(|process| process.pid)
+    }
+}
+
+impl DetectedGame {
+    /// A custom game without any metadata besides its id.
+    pub fn custom(id: impl Into<String>) -> Self {
+        DetectedGame::Custom { id: id.into(), info: Default::default() }
+    }
+
+    pub fn platform(&self) -> GamePlatform {
+        match self {
+            #[cfg(feature = "steam")]
+            DetectedGame::Steam { .. } | DetectedGame::SteamShortcut { .. } => GamePlatform::Steam,
+            #[cfg(feature = "gamejolt")]
+            DetectedGame::GameJolt { .. } => GamePlatform::GameJolt,
+            #[cfg(feature = "itchio")]
+            DetectedGame::ItchIo { .. } => GamePlatform::ItchIo,
+            #[cfg(feature = "lutris")]
+            DetectedGame::Lutris { .. } => GamePlatform::Lutris,
+            #[cfg(feature = "roblox")]
+            DetectedGame::Roblox { .. } => GamePlatform::Roblox,
+            #[cfg(feature = "minecraft")]
+            DetectedGame::Minecraft { .. } | DetectedGame::MinecraftDungeons { .. } | DetectedGame::MinecraftLegends { .. } => GamePlatform::MinecraftLauncher,
+            DetectedGame::Custom { .. } => GamePlatform::Custom,
+        }
+    }
 }
 
 #[cfg(feature = "http")]
 impl DetectedGame {
-    pub async fn get_info(&self) -> Result<GameInfo> {
+    pub async fn get_info(&self) -> Result<GameInfo, FetchError> {
+        self.get_info_with(&InfoOptions::default()).await
+    }
+
+    /// Like [`DetectedGame::get_info`], but localized and reading local files where `options`
+    /// points. For Steam games this reads custom artwork, and the metadata cache when the store
+    /// can't be reached, with blocking file I/O on the calling thread. Callers on an async runtime
+    /// that must not block should call it from a blocking task, such as tokio's `spawn_blocking`.
+    #[cfg_attr(not(feature = "steam"), allow(unused_variables))]
+    pub async fn get_info_with(&self, options: &InfoOptions) -> Result<GameInfo, FetchError> {
         match self {
             #[cfg(feature = "steam")]
-            DetectedGame::Steam { .. } => platforms::steam::fetch_info(&self).await,
+            DetectedGame::Steam { .. } | DetectedGame::SteamShortcut { .. } => platforms::steam::fetch_info(self, options).await,
             #[cfg(feature = "gamejolt")]
-            DetectedGame::GameJolt { .. } => platforms::gamejolt::fetch_info(&self).await,
+            DetectedGame::GameJolt { .. } => platforms::gamejolt::fetch_info(self).await,
             #[cfg(feature = "itchio")]
-            DetectedGame::ItchIo { .. } => platforms::itchio::fetch_info(&self).await,
+            DetectedGame::ItchIo { .. } => platforms::itchio::fetch_info(self).await,
             #[cfg(feature = "lutris")]
-            DetectedGame::Lutris { .. } => platforms::lutris::fetch_info(&self).await,
+            DetectedGame::Lutris { .. } => platforms::lutris::fetch_info(self).await,
             #[cfg(feature = "roblox")]
-            DetectedGame::Roblox { .. } => platforms::roblox::fetch_info(&self).await,
+            DetectedGame::Roblox { .. } => platforms::roblox::fetch_info(self).await,
             #[cfg(feature = "minecraft")]
-            DetectedGame::Minecraft { .. } => platforms::minecraft::fetch_info(&self).await,
+            DetectedGame::Minecraft { .. } => platforms::minecraft::fetch_info(self).await,
             #[cfg(feature = "minecraft")]
-            DetectedGame::MinecraftDungeons { .. } => platforms::minecraft::fetch_info(&self).await,
+            DetectedGame::MinecraftDungeons { .. } => platforms::minecraft::fetch_info(self).await,
             #[cfg(feature = "minecraft")]
-            DetectedGame::MinecraftLegends { .. } => platforms::minecraft::fetch_info(&self).await,
-            DetectedGame::Custom(id) => Ok(GameInfo {
-                app_id: None,
+            DetectedGame::MinecraftLegends { .. } => platforms::minecraft::fetch_info(self).await,
+            DetectedGame::Custom { id, info } => Ok(GameInfo {
+                app_id: info.app_id,
                 via_platform: GamePlatform::Custom,
-                name: id.clone(),
-                description: "".to_string(),
-                cover: "".to_string(),
-                icon: "".to_string(),
-                developers: vec![],
-                publishers: vec![],
+                name: if info.name.is_empty() { id.clone() } else { info.name.clone() },
+                description: info.description.clone(),
+                cover: info.cover.clone(),
+                icon: info.icon.clone(),
+                developers: info.developers.clone(),
+                publishers: info.publishers.clone(),
                 required_age: None,
-                url: "".to_string(),
+                url: info.url.clone(),
             }),
         }
     }
@@ -134,69 +242,204 @@ impl DetectedGame {
 impl PartialEq for DetectedGame {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
+            #[cfg(feature = "steam")]
             (DetectedGame::Steam { id: id1, .. }, DetectedGame::Steam { id: id2, .. }) => id1 == id2,
+            #[cfg(feature = "steam")]
+            (DetectedGame::SteamShortcut { id: id1, .. }, DetectedGame::SteamShortcut { id: id2, .. }) => id1 == id2,
+            #[cfg(feature = "gamejolt")]
             (DetectedGame::GameJolt { id: id1, .. }, DetectedGame::GameJolt { id: id2, .. }) => id1 == id2,
+            #[cfg(feature = "itchio")]
             (DetectedGame::ItchIo { id: id1, .. }, DetectedGame::ItchIo { id: id2, .. }) => id1 == id2,
+            #[cfg(feature = "lutris")]
             (DetectedGame::Lutris { id: id1, .. }, DetectedGame::Lutris { id: id2, .. }) => id1 == id2,
+            #[cfg(feature = "roblox")]
             (DetectedGame::Roblox { id: id1, .. }, DetectedGame::Roblox { id: id2, .. }) => id1 == id2,
+            #[cfg(feature = "minecraft")]
             (DetectedGame::Minecraft { .. }, DetectedGame::Minecraft { .. }) => true,
+            #[cfg(feature = "minecraft")]
             (DetectedGame::MinecraftDungeons { .. }, DetectedGame::MinecraftDungeons { .. }) => true,
+            #[cfg(feature = "minecraft")]
             (DetectedGame::MinecraftLegends { .. }, DetectedGame::MinecraftLegends { .. }) => true,
+            (DetectedGame::Custom { id: id1, .. }, DetectedGame::Custom { id: id2, .. }) => id1 == id2,
+            #[allow(unreachable_patterns)]
             _ => false,
         }
     }
 }
 
-pub fn detect_game(custom_games: Option<HashMap<String, String>>) -> Result<Option<DetectedGame>> {
-    let processes = procfs::process::all_processes().unwrap();
-    let processes: Vec<procfs::process::Process> = processes.filter_map(|process| process.ok()).collect();
-
-    if custom_games.is_some() {
-        for (id, name) in custom_games.unwrap() {
-            for process in &processes {
-                let cmd = process.cmdline().ok();
-                match cmd {
-                    Some(cmd) => {
-                        let cmd = cmd.join(" ");
-                        if cmd.to_lowercase().contains(&name.to_lowercase()) {
-                            return Ok(Some(DetectedGame::Custom(id)));
-                        }
-                    }
-                    None => continue,
-                }
-            }
+// Hashing has to agree with the id-based equality above, so only the variant and its id are hashed.
+impl Hash for DetectedGame {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            #[cfg(feature = "steam")]
+            DetectedGame::Steam { id, .. } | DetectedGame::SteamShortcut { id, .. } => id.hash(state),
+            #[cfg(feature = "gamejolt")]
+            DetectedGame::GameJolt { id, .. } => id.hash(state),
+            #[cfg(feature = "itchio")]
+            DetectedGame::ItchIo { id, .. } => id.hash(state),
+            #[cfg(feature = "lutris")]
+            DetectedGame::Lutris { id, .. } => id.hash(state),
+            #[cfg(feature = "roblox")]
+            DetectedGame::Roblox { id, .. } => id.hash(state),
+            DetectedGame::Custom { id, .. } => id.hash(state),
+            #[allow(unreachable_patterns)]
+            _ => {}
         }
     }
-    
-    #[cfg(feature = "steam")]
-    if let Some(game) = platforms::steam::detect_game()? {
-        return Ok(Some(game));
-    }
+}
 
-    #[cfg(feature = "itchio")]
-    if let Some(game) = platforms::itchio::detect_game(&processes)? {
-        return Ok(Some(game));
-    }
+pub fn detect_game(custom_games: Option<HashMap<String, String>>) -> Result<Option<DetectedGame>, DetectError> {
+    registry(custom_games).detect()
+}
 
-    #[cfg(feature = "gamejolt")]
-    if let Some(game) = platforms::gamejolt::detect_game(&processes)? {
-        return Ok(Some(game));
-    }
+/// Like [`detect_game`], but returns every running game instead of stopping at the first one.
+pub fn detect_all_games(custom_games: Option<HashMap<String, String>>) -> Result<Vec<Detection>, DetectError> {
+    registry(custom_games).detect_all()
+}
 
-    #[cfg(all(feature = "lutris", target_os = "linux"))]
-    if let Some(game) = platforms::lutris::detect_game(&processes)? {
-        return Ok(Some(game));
-    }
+/// Like [`detect_game`], but matches custom games with [`platforms::custom::CustomGameRule`]s.
+pub fn detect_game_with_rules(rules: Vec<platforms::custom::CustomGameRule>) -> Result<Option<DetectedGame>, DetectError> {
+    rules_registry(rules).detect()
+}
 
-    #[cfg(feature = "roblox")]
-    if let Some(game) = platforms::roblox::detect_game(&processes)? {
-        return Ok(Some(game));
+/// Like [`detect_all_games`], but matches custom games with [`platforms::custom::CustomGameRule`]s.
+pub fn detect_all_games_with_rules(rules: Vec<platforms::custom::CustomGameRule>) -> Result<Vec<Detection>, DetectError> {
+    rules_registry(rules).detect_all()
+}
+
+/// Like [`detect_game`], but reads processes and launcher data from where `options` points.
+pub fn detect_game_with_options(custom_games: Option<HashMap<String, String>>, options: &DetectOptions) -> Result<Option<DetectedGame>, DetectError> {
+    registry(custom_games).detect_with(&DetectContext::with_options(options)?)
+}
+
+/// Like [`detect_all_games`], but reads processes and launcher data from where `options` points.
+pub fn detect_all_games_with_options(custom_games: Option<HashMap<String, String>>, options: &DetectOptions) -> Result<Vec<Detection>, DetectError> {
+    registry(custom_games).detect_all_with(&DetectContext::with_options(options)?)
+}
+
+/// Like [`detect_game`], but runs against a snapshot captured with [`snapshot::capture`] instead of
+/// the live system.
+#[cfg(feature = "snapshot")]
+pub fn detect_game_from_snapshot(dir: impl AsRef<std::path::Path>, custom_games: Option<HashMap<String, String>>) -> Result<Option<DetectedGame>, DetectError> {
+    registry(custom_games).detect_with(&snapshot::load(dir)?)
+}
+
+fn registry(custom_games: Option<HashMap<String, String>>) -> DetectorRegistry {
+    let mut registry = DetectorRegistry::new();
+    if let Some(custom_games) = custom_games {
+        registry.insert(0, platforms::custom::CustomDetector::new(custom_games));
     }
+    registry
+}
 
-    #[cfg(feature = "minecraft")]
-    if let Some(game) = platforms::minecraft::detect_game(&processes)? {
-        return Ok(Some(game));
+fn rules_registry(rules: Vec<platforms::custom::CustomGameRule>) -> DetectorRegistry {
+    let mut registry = DetectorRegistry::new();
+    registry.insert(0, platforms::custom::CustomDetector::with_rules(rules));
+    registry
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    // `DetectedGame` only compares ids, so round trips are checked on the debug output instead.
+    fn round_trip<T: serde::Serialize + serde::de::DeserializeOwned + std::fmt::Debug>(value: &T) -> String {
+        let json = serde_json::to_string(value).unwrap();
+        let parsed: T = serde_json::from_str(&json).unwrap();
+        assert_eq!(format!("{:?}", parsed), format!("{:?}", value));
+        json
     }
 
-    Ok(None)
+    #[test]
+    fn test_serde_round_trip() {
+        let games = vec![
+            #[cfg(feature = "steam")]
+            DetectedGame::Steam { id: 570, url: "https://store.steampowered.com/app/570".to_owned(), icon: "icon".to_owned(), compatibility: None },
+            #[cfg(feature = "steam")]
+            DetectedGame::SteamShortcut {
+                id: 3060399406,
+                shortcut: Box::new(platforms::steam::Shortcut {
+                    app_id: 3060399406,
+                    user_id: 42,
+                    name: "name".to_owned(),
+                    exe: "/opt/game/game".to_owned(),
+                    start_dir: "/opt/game".to_owned(),
+                    launch_options: "--fullscreen".to_owned(),
+                    icon: "icon".to_owned(),
+                    tags: vec!["tag".to_owned()],
+                    last_played: Some(std::time::SystemTime::UNIX_EPOCH),
+                    artwork: platforms::steam::GridArtwork { icon: Some("icon.png".into()), ..Default::default() },
+                }),
+            },
+            #[cfg(feature = "gamejolt")]
+            DetectedGame::GameJolt {
+                id: 303626,
+                url: "url".to_owned(),
+                name: "name".to_owned(),
+                cover: "cover".to_owned(),
+                icon: "icon".to_owned(),
+                developers: vec!["developer".to_owned()],
+                publishers: vec!["publisher".to_owned()],
+            },
+            #[cfg(feature = "itchio")]
+            DetectedGame::ItchIo {
+                id: 1,
+                url: "url".to_owned(),
+                name: "name".to_owned(),
+                description: "description".to_owned(),
+                cover: "cover".to_owned(),
+                icon: "icon".to_owned(),
+                developers: vec!["developer".to_owned()],
+                publishers: vec![],
+            },
+            #[cfg(feature = "lutris")]
+            DetectedGame::Lutris { id: 2, slug: "slug".to_owned(), name: "name".to_owned(), cover: "cover".to_owned(), icon: "icon".to_owned() },
+            #[cfg(feature = "roblox")]
+            DetectedGame::Roblox { id: 1818, url: "https://roblox.com/games/1818".to_owned() },
+            #[cfg(feature = "minecraft")]
+            DetectedGame::Minecraft { cover: "cover".to_owned(), icon: "icon".to_owned() },
+            #[cfg(feature = "minecraft")]
+            DetectedGame::MinecraftDungeons { cover: "cover".to_owned(), icon: "icon".to_owned() },
+            #[cfg(feature = "minecraft")]
+            DetectedGame::MinecraftLegends { cover: "cover".to_owned(), icon: "icon".to_owned() },
+            DetectedGame::custom("custom"),
+            DetectedGame::Custom {
+                id: "custom".to_owned(),
+                info: Box::new(platforms::custom::CustomGameInfo {
+                    name: "name".to_owned(),
+                    cover: "cover".to_owned(),
+                    developers: vec!["developer".to_owned()],
+                    app_id: Some(570),
+                    ..Default::default()
+                }),
+            },
+        ];
+        for game in games {
+            round_trip(&game);
+            round_trip(&game.platform());
+            round_trip(&Detection::new(game.clone(), Some(ProcessInfo { pid: 1, cmdline: vec!["game".to_owned()], ..Default::default() })));
+            round_trip(&GameInfo {
+                app_id: Some(570),
+                via_platform: game.platform(),
+                name: "name".to_owned(),
+                description: "description".to_owned(),
+                cover: "cover".to_owned(),
+                icon: "icon".to_owned(),
+                developers: vec!["developer".to_owned()],
+                publishers: vec!["publisher".to_owned()],
+                required_age: Some(18),
+                url: "url".to_owned(),
+            });
+        }
+
+        assert!(round_trip(&DetectedGame::custom("custom")).starts_with(r#"{"platform":"custom","game":{"id":"custom","info":{"#));
+        #[cfg(feature = "steam")]
+        assert_eq!(round_trip(&GamePlatform::Steam), r#""steam""#);
+
+        let mut process = ProcessInfo { pid: 1, ..Default::default() };
+        process.environ.insert("API_TOKEN".to_owned(), "secret".to_owned());
+        let json = serde_json::to_string(&Detection::new(DetectedGame::custom("custom"), Some(process))).unwrap();
+        assert!(!json.contains("secret"), "{json}");
+    }
 }
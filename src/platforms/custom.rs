@@ -0,0 +1,287 @@
+use std::collections::{BTreeMap, HashMap};
+
+use glob::{MatchOptions, Pattern};
+use regex::Regex;
+
+use crate::{DetectContext, DetectError, DetectedGame, Detection, Detector, ProcessEntry, ProcessSnapshot};
+
+/// A set of conditions on a process. Every condition that is set has to hold for the matcher to
+/// match.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessMatcher {
+    /// The exact file name of the executable, such as `game.x86_64`. Also checked against the file
+    /// name of the first argument, which is how games running under Wine show up.
+    pub exe_name: Option<String>,
+    /// A glob over the full executable path, such as `/opt/games/**/game.x86_64`. As in a shell,
+    /// `*` stays within one directory and `**` matches any depth.
+    pub exe_path: Option<Pattern>,
+    /// Searched for in the command line, joined with spaces.
+    pub cmdline: Option<Regex>,
+    /// Text the command line has to contain, ignoring case.
+    pub cmdline_contains: Option<String>,
+    /// A glob over the working directory.
+    pub cwd: Option<Pattern>,
+    /// Environment variables that have to be set to exactly these values.
+    pub env: BTreeMap<String, String>,
+    /// A matcher the parent process has to match.
+    pub parent: Option<Box<ProcessMatcher>>,
+}
+
+// Without `require_literal_separator`, `*` also matches `/` and so behaves like `**`.
+const PATH_MATCH: MatchOptions = MatchOptions { case_sensitive: true, require_literal_separator: true, require_literal_leading_dot: false };
+
+impl ProcessMatcher {
+    pub fn is_empty(&self) -> bool {
+        self.exe_name.is_none() && self.exe_path.is_none() && self.cmdline.is_none() && self.cmdline_contains.is_none()
+            && self.cwd.is_none() && self.env.is_empty() && self.parent.is_none()
+    }
+
+    fn reads_environ(&self, key: &str) -> bool {
+        self.env.contains_key(key) || self.parent.as_ref().is_some_and(|parent| parent.reads_environ(key))
+    }
+
+    fn matches(&self, process: ProcessEntry, processes: &ProcessSnapshot) -> bool {
+        if let Some(exe_name) = &self.exe_name {
+            if !processes.by_exe_name(exe_name).any(|candidate| candidate.info.pid == process.info.pid) {
+                return false;
+            }
+        }
+        if let Some(exe_path) = &self.exe_path {
+            if !process.info.exe.as_ref().is_some_and(|exe| exe_path.matches_path_with(exe, PATH_MATCH)) {
+                return false;
+            }
+        }
+        if let Some(cmdline) = &self.cmdline {
+            if !cmdline.is_match(process.cmdline) {
+                return false;
+            }
+        }
+        if let Some(text) = &self.cmdline_contains {
+            if !process.lowercase_cmdline.contains(&text.to_lowercase()) {
+                return false;
+            }
+        }
+        if let Some(cwd) = &self.cwd {
+            if !process.info.cwd.as_ref().is_some_and(|dir| cwd.matches_path_with(dir, PATH_MATCH)) {
+                return false;
+            }
+        }
+        if !self.env.iter().all(|(key, value)| process.info.environ.get(key) == Some(value)) {
+            return false;
+        }
+        if let Some(parent) = &self.parent {
+            let parent_process = process.info.ppid.and_then(|ppid| processes.get(ppid));
+            if !parent_process.is_some_and(|parent_process| parent.matches(parent_process, processes)) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Metadata for a custom game, returned by [`DetectedGame::get_info`]. An empty `name` falls back
+/// to the game's id.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct CustomGameInfo {
+    pub name: String,
+    pub description: String,
+    pub cover: String,
+    pub icon: String,
+    pub developers: Vec<String>,
+    pub publishers: Vec<String>,
+    pub url: String,
+    /// A Steam app id, if the game is also on Steam.
+    pub app_id: Option<i64>,
+}
+
+/// A user-defined game, detected in every process that matches `matcher` and none of `exclude`.
+/// A rule with an empty matcher never matches.
+#[derive(Debug, Clone)]
+pub struct CustomGameRule {
+    pub id: String,
+    pub info: Box<CustomGameInfo>,
+    pub matcher: ProcessMatcher,
+    pub exclude: Vec<ProcessMatcher>,
+}
+
+impl CustomGameRule {
+    pub fn new(id: impl Into<String>, matcher: ProcessMatcher) -> Self {
+        Self { id: id.into(), info: Box::default(), matcher, exclude: vec![] }
+    }
+
+    pub fn with_info(mut self, info: CustomGameInfo) -> Self {
+        self.info = Box::new(info);
+        self
+    }
+
+    /// Matches processes whose command line contains `name`, ignoring case.
+    pub fn cmdline_contains(id: impl Into<String>, name: &str) -> Self {
+        Self::new(id, ProcessMatcher { cmdline_contains: Some(name.to_owned()), ..Default::default() })
+    }
+
+    pub fn exclude(mut self, matcher: ProcessMatcher) -> Self {
+        self.exclude.push(matcher);
+        self
+    }
+
+    fn matches(&self, process: ProcessEntry, processes: &ProcessSnapshot) -> bool {
+        !self.matcher.is_empty()
+            && self.matcher.matches(process, processes)
+            && !self.exclude.iter().any(|exclude| exclude.matches(process, processes))
+    }
+}
+
+/// Matches user-defined games with [`CustomGameRule`]s.
+pub struct CustomDetector {
+    rules: Vec<CustomGameRule>,
+}
+
+impl CustomDetector {
+    /// Matches each game by a case-insensitive substring of the process command line, keyed by id.
+    pub fn new(games: HashMap<String, String>) -> Self {
+        Self::with_rules(games.into_iter().map(|(id, name)| CustomGameRule::cmdline_contains(id, &name)).collect())
+    }
+
+    pub fn with_rules(rules: Vec<CustomGameRule>) -> Self {
+        Self { rules }
+    }
+}
+
+impl Detector for CustomDetector {
+    fn name(&self) -> &str {
+        "custom"
+    }
+
+    fn detect(&self, context: &DetectContext) -> Result<Vec<Detection>, DetectError> {
+        detect_games(context, &self.rules)
+    }
+
+    fn reads_environ(&self, key: &str) -> bool {
+        self.rules.iter().any(|rule| rule.matcher.reads_environ(key) || rule.exclude.iter().any(|exclude| exclude.reads_environ(key)))
+    }
+}
+
+pub fn detect_games(context: &DetectContext, rules: &[CustomGameRule]) -> Result<Vec<Detection>, DetectError> {
+    let processes = context.processes();
+    let mut detections = vec![];
+    for rule in rules {
+        // Rules naming an executable only need to look at the processes running it.
+        let candidates: Vec<ProcessEntry> = match &rule.matcher.exe_name {
+            Some(exe_name) => processes.by_exe_name(exe_name).collect(),
+            None => processes.iter().collect(),
+        };
+        for process in candidates {
+            if rule.matches(process, processes) {
+                let game = DetectedGame::Custom { id: rule.id.clone(), info: rule.info.clone() };
+                detections.push(Detection::new(game, Some(process.info.clone())));
+            }
+        }
+    }
+    Ok(detections)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProcessInfo;
+    use std::path::PathBuf;
+
+    fn process(pid: i32, ppid: i32, exe: &str, cmdline: &[&str]) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            ppid: Some(ppid),
+            exe: Some(PathBuf::from(exe)),
+            cmdline: cmdline.iter().map(|arg| arg.to_string()).collect(),
+            cwd: Some(PathBuf::from("/home/user")),
+            ..Default::default()
+        }
+    }
+
+    fn detected(rules: Vec<CustomGameRule>, processes: &[ProcessInfo]) -> Vec<(String, i32)> {
+        let context = DetectContext::new(processes.to_vec(), None);
+        CustomDetector::with_rules(rules).detect(&context).unwrap().into_iter()
+            .map(|detection| match detection.game {
+                DetectedGame::Custom { ref id, .. } => (id.clone(), detection.pid().unwrap()),
+                #[allow(unreachable_patterns)]
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rules() {
+        let mut game = process(3, 2, "/opt/games/celeste/Celeste", &["/opt/games/celeste/Celeste"]);
+        game.cwd = Some(PathBuf::from("/opt/games/celeste"));
+        game.environ.insert("SDL_VIDEODRIVER".to_owned(), "wayland".to_owned());
+        let processes = [
+            process(1, 0, "/usr/bin/bash", &["bash"]),
+            process(2, 1, "/usr/bin/steam", &["steam"]),
+            game,
+            process(4, 1, "/usr/bin/vim", &["vim", "/opt/games/celeste/Celeste.txt"]),
+            process(5, 1, "/usr/bin/wine64-preloader", &["Z:\\games\\Hollow Knight\\hollow_knight.exe"]),
+        ];
+
+        assert_eq!(detected(vec![CustomGameRule::cmdline_contains("celeste", "CELESTE")], &processes), vec![
+            ("celeste".to_owned(), 3),
+            ("celeste".to_owned(), 4),
+        ]);
+        assert!(detected(vec![CustomGameRule::cmdline_contains("long", &"a".repeat(100_000))], &processes).is_empty());
+        assert!(detected(vec![CustomGameRule::cmdline_contains("regex", "celeste.*")], &processes).is_empty());
+
+        let rule = CustomGameRule::new("celeste", ProcessMatcher { exe_name: Some("Celeste".to_owned()), ..Default::default() });
+        assert_eq!(detected(vec![rule], &processes), vec![("celeste".to_owned(), 3)]);
+        let rule = CustomGameRule::new("hollow_knight", ProcessMatcher { exe_name: Some("hollow_knight.exe".to_owned()), ..Default::default() });
+        assert_eq!(detected(vec![rule], &processes), vec![("hollow_knight".to_owned(), 5)]);
+
+        let rule = CustomGameRule::new("celeste", ProcessMatcher {
+            exe_path: Some(Pattern::new("/opt/games/**/Celeste").unwrap()),
+            cwd: Some(Pattern::new("/opt/games/*").unwrap()),
+            env: BTreeMap::from([("SDL_VIDEODRIVER".to_owned(), "wayland".to_owned())]),
+            parent: Some(Box::new(ProcessMatcher { exe_name: Some("steam".to_owned()), ..Default::default() })),
+            ..Default::default()
+        });
+        assert_eq!(detected(vec![rule.clone()], &processes), vec![("celeste".to_owned(), 3)]);
+        assert!(CustomDetector::with_rules(vec![rule.clone()]).reads_environ("SDL_VIDEODRIVER"));
+        assert!(!CustomDetector::with_rules(vec![rule.clone()]).reads_environ("HOME"));
+        let mut nested = processes.clone();
+        nested[2].cwd = Some(PathBuf::from("/opt/games/celeste/Content"));
+        assert!(detected(vec![rule.clone()], &nested).is_empty());
+        let deep = CustomGameRule::new("celeste", ProcessMatcher { cwd: Some(Pattern::new("/opt/games/**").unwrap()), ..Default::default() });
+        assert_eq!(detected(vec![deep], &nested), vec![("celeste".to_owned(), 3)]);
+        let rule = rule.exclude(ProcessMatcher { env: BTreeMap::from([("SDL_VIDEODRIVER".to_owned(), "wayland".to_owned())]), ..Default::default() });
+        assert!(detected(vec![rule], &processes).is_empty());
+
+        let rule = CustomGameRule::new("celeste", ProcessMatcher {
+            cmdline: Some(Regex::new("(?i)celeste").unwrap()),
+            ..Default::default()
+        }).exclude(ProcessMatcher { exe_name: Some("vim".to_owned()), ..Default::default() });
+        assert_eq!(detected(vec![rule], &processes), vec![("celeste".to_owned(), 3)]);
+
+        assert!(detected(vec![CustomGameRule::new("everything", ProcessMatcher::default())], &processes).is_empty());
+    }
+
+    #[actix_rt::test]
+    #[cfg(feature = "http")]
+    async fn test_get_info() {
+        let rule = CustomGameRule::new("celeste", ProcessMatcher { exe_name: Some("Celeste".to_owned()), ..Default::default() })
+            .with_info(CustomGameInfo {
+                name: "Celeste".to_owned(),
+                developers: vec!["Maddy Makes Games".to_owned()],
+                url: "https://www.celestegame.com".to_owned(),
+                app_id: Some(504230),
+                ..Default::default()
+            });
+        let context = DetectContext::new(vec![process(1, 0, "/opt/games/celeste/Celeste", &["./Celeste"])], None);
+        let detection = CustomDetector::with_rules(vec![rule]).detect(&context).unwrap().remove(0);
+        let info = detection.game.get_info().await.unwrap();
+        assert_eq!(info.name, "Celeste");
+        assert_eq!(info.developers, vec!["Maddy Makes Games"]);
+        assert_eq!(info.url, "https://www.celestegame.com");
+        assert_eq!(info.app_id, Some(504230));
+        assert_eq!(info.via_platform, crate::GamePlatform::Custom);
+
+        let info = DetectedGame::custom("celeste").get_info().await.unwrap();
+        assert_eq!(info.name, "celeste");
+    }
+}
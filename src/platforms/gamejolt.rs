@@ -1,14 +1,9 @@
 #[cfg(feature = "http")]
-use crate::{GameInfo, GamePlatform};
-use std::{collections::HashMap, fs::{exists, read_to_string}};
-use anyhow::Result;
-#[cfg(any(target_os = "linux", target_os = "macos"))]
-use home::home_dir;
-#[cfg(target_family = "windows")]
-use dirs::data_local_dir;
+use crate::{FetchError, GameInfo, GamePlatform};
+use std::{collections::HashMap, fs::read_to_string, path::{Path, PathBuf}};
 use serde::Deserialize;
 
-use crate::DetectedGame;
+use crate::{DetectContext, DetectError, DetectedGame, Detection, Detector};
 
 #[derive(Deserialize)]
 struct Package {
@@ -45,50 +40,79 @@ struct Games {
     pub objects: HashMap<i64, Game>
 }
 
-pub fn detect_game(processes: &Vec<procfs::process::Process>) -> Result<Option<DetectedGame>> {
-    #[cfg(target_family = "windows")]
-    let data_dir = data_local_dir().unwrap().join("game-jolt-client").join("Default");
-    #[cfg(target_os = "linux")]
-    let data_dir = home_dir().unwrap().join(".config").join("game-jolt-client").join("Default");
-    #[cfg(target_os = "macos")]
-    let data_dir = home_dir().unwrap().join("Library").join("Application Support").join("game-jolt-client").join("Default");
+pub struct GameJoltDetector;
+
+impl Detector for GameJoltDetector {
+    fn name(&self) -> &str {
+        "gamejolt"
+    }
+
+    fn detect(&self, context: &DetectContext) -> Result<Vec<Detection>, DetectError> {
+        detect_games(context)
+    }
+
+    fn data_files(&self, context: &DetectContext) -> Vec<PathBuf> {
+        match data_dir(context) {
+            Ok(data_dir) => vec![data_dir.join("packages.wttf"), data_dir.join("games.wttf")],
+            Err(_) => vec![],
+        }
+    }
+}
+
+fn data_dir(context: &DetectContext) -> Result<PathBuf, DetectError> {
+    Ok(context.app_data_dir()?.join("game-jolt-client").join("Default"))
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T, DetectError> {
+    let content = read_to_string(path).map_err(|e| DetectError::io(path, e))?;
+    serde_json::from_str(&content).map_err(|e| DetectError::parse(path, e))
+}
+
+pub fn detect_games(context: &DetectContext) -> Result<Vec<Detection>, DetectError> {
+    let mut detections = vec![];
+    let data_dir = context.path(data_dir(context)?);
 
-    if !exists(&data_dir).unwrap_or(false) {
-        return Ok(None);
+    if !data_dir.exists() {
+        return Ok(detections);
     }
 
-    let packages: Packages = serde_json::from_str(&read_to_string(&data_dir.join("packages.wttf"))?)?;
-    let games: Games = serde_json::from_str(&read_to_string(&data_dir.join("games.wttf"))?)?;
+    let packages: Packages = read_json(&data_dir.join("packages.wttf"))?;
+    let games: Games = read_json(&data_dir.join("games.wttf"))?;
 
     for (_id, package) in packages.objects {
-        if !&package.running_pid.is_some() {
+        // The client stores the PID with a two character prefix. A package with a PID that can't be
+        // read is skipped rather than hiding the other games.
+        let Some(running_pid) = package.running_pid.as_ref()
+            .and_then(|running_pid| running_pid.get(2..))
+            .and_then(|pid| pid.parse::<i32>().ok()) else {
             continue;
-        }
-        for process in processes {
-            if &process.pid == &package.running_pid.as_ref().unwrap()[2..].parse::<i32>()? {
-                let game_id = package.game_id;
-                let game_details = match games.objects.get(&game_id) {
-                    Some(game) => game,
-                    None => continue
-                };
+        };
+        let Some(process) = context.processes().get(running_pid) else {
+            continue;
+        };
+        let game_id = package.game_id;
+        let game_details = match games.objects.get(&game_id) {
+            Some(game) => game,
+            None => continue
+        };
 
-                return Ok(Some(DetectedGame::GameJolt {
-                    id: game_id.clone(),
-                    name: game_details.title.clone(),
-                    url: format!("https://gamejolt.com/games/{0}/{1}", game_details.slug.clone().unwrap_or("redirect".to_owned()), game_id),
-                    developers: vec![game_details.developer.display_name.clone()],
-                    publishers: vec![game_details.developer.display_name.clone()],
-                    icon: game_details.header_media_item.img_url.clone(),
-                    cover: game_details.thumbnail_media_item.img_url.clone()
-                }))
-            }
-        }
+        detections.push(Detection::new(DetectedGame::GameJolt {
+            id: game_id,
+            name: game_details.title.clone(),
+            url: format!("https://gamejolt.com/games/{0}/{1}", game_details.slug.clone().unwrap_or("redirect".to_owned()), game_id),
+            developers: vec![game_details.developer.display_name.clone()],
+            publishers: vec![game_details.developer.display_name.clone()],
+            icon: game_details.header_media_item.img_url.clone(),
+            cover: game_details.thumbnail_media_item.img_url.clone()
+        }, Some(process.info.clone())));
     }
-    Ok(None)
+    Ok(detections)
 }
 
 #[cfg(feature = "http")]
 mod http {
+    use std::fmt::{self, Display};
+
     use serde::Deserialize;
 
     #[derive(Deserialize)]
@@ -159,9 +183,9 @@ mod http {
         },
     }
 
-    impl ToString for MarkdownTag {
-        fn to_string(&self) -> String {
-            match self {
+    impl Display for MarkdownTag {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let text = match self {
                 MarkdownTag::Paragraph { content } => {
                     content.iter().map(|tag| tag.to_string()).collect::<Vec<String>>().join("") + "\n"
                 }
@@ -179,9 +203,7 @@ mod http {
                                 MarkdownMark::Italic => {
                                     text = format!("*{}*", text);
                                 }
-                                MarkdownMark::Tag { .. } => {
-                                    text = text;
-                                }
+                                MarkdownMark::Tag { .. } => {}
                             }
                         }
                     }
@@ -194,7 +216,8 @@ mod http {
                 MarkdownTag::ListItem { content } => {
                     "• ".to_owned() + &content.iter().map(|tag| tag.to_string()).collect::<Vec<String>>().join("\n")
                 }
-            }
+            };
+            f.write_str(&text)
         }
     }
 
@@ -203,9 +226,9 @@ mod http {
         pub content: Vec<MarkdownTag>
     }
 
-    impl ToString for Description {
-        fn to_string(&self) -> String {
-            self.content.iter().map(|tag| tag.to_string()).collect::<Vec<String>>().join("\n")
+    impl Display for Description {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(&self.content.iter().map(|tag| tag.to_string()).collect::<Vec<String>>().join("\n"))
         }
     }
 
@@ -232,7 +255,7 @@ mod http {
 }
 
 #[cfg(feature = "http")]
-pub(crate) async fn fetch_info(detected: &DetectedGame) -> Result<GameInfo> {
+pub(crate) async fn fetch_info(detected: &DetectedGame) -> Result<GameInfo, FetchError> {
     match detected {
         DetectedGame::GameJolt { id, .. } => {
             let client = reqwest::Client::new();
@@ -261,10 +284,10 @@ pub(crate) async fn fetch_info(detected: &DetectedGame) -> Result<GameInfo> {
                     url: format!("https://gamejolt.com/games/{0}/{1}", game.slug.unwrap_or("redirect".to_owned()), id)
                 })
             } else {
-                return Err(anyhow::anyhow!("Failed to fetch game info"));
+                Err(FetchError::Status(response.status()))
             }
         }
-        _ => unreachable!()
+        _ => Err(FetchError::Unsupported(detected.platform()))
     }
 }
 
@@ -272,6 +295,28 @@ pub(crate) async fn fetch_info(detected: &DetectedGame) -> Result<GameInfo> {
 mod tests {
     use super::*;
 
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_detect_games() {
+        use crate::testing::{process, TempDir};
+
+        let home = TempDir::new("gamejolt");
+        let data_dir = home.join(".config/game-jolt-client/Default");
+        std::fs::create_dir_all(&data_dir).unwrap();
+        std::fs::write(data_dir.join("packages.wttf"), r#"{"objects": {
+            "1": {"game_id": 10, "running_pid": "x:broken"},
+            "2": {"game_id": 20, "running_pid": "x:42"},
+            "3": {"game_id": 30}
+        }}"#).unwrap();
+        let game = |title: &str| format!(r#"{{"title": "{title}", "developer": {{"display_name": "dev"}}, "header_media_item": {{"img_url": "header"}}, "thumbnail_media_item": {{"img_url": "thumbnail"}}}}"#);
+        std::fs::write(data_dir.join("games.wttf"), format!(r#"{{"objects": {{"10": {}, "20": {}, "30": {}}}}}"#, game("Broken"), game("Running"), game("Idle"))).unwrap();
+
+        let context = DetectContext::new(vec![process(42, 1, "/opt/game", &["/opt/game"])], Some(home.to_path_buf()));
+        let detections = detect_games(&context).unwrap();
+        assert_eq!(detections.len(), 1);
+        assert!(matches!(&detections[0].game, DetectedGame::GameJolt { id: 20, name, .. } if name == "Running"));
+    }
+
     #[actix_rt::test]
     #[cfg(feature = "http")]
     async fn test_fetch_info() {
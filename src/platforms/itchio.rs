@@ -1,15 +1,9 @@
 #[cfg(feature = "http")]
-use crate::{GameInfo, GamePlatform};
-use std::{fs::{exists, read_to_string}, io::Read};
-use anyhow::Result;
-#[cfg(any(target_os = "linux", target_os = "macos"))]
-use home::home_dir;
-#[cfg(target_family = "windows")]
-use dirs::data_local_dir;
+use crate::{FetchError, GameInfo, GamePlatform};
+use std::{fs::{read, read_dir, read_to_string}, io::Read, path::{Path, PathBuf}};
 use serde::Deserialize;
-use regex::Regex;
 
-use crate::DetectedGame;
+use crate::{DetectContext, DetectError, DetectedGame, Detection, Detector};
 
 #[derive(Deserialize)]
 struct PartialPreferences {
@@ -40,84 +34,129 @@ struct GameReceipt {
     pub game: ReceiptInner
 }
 
-fn escape_str(s: &str) -> String {
-    let re = Regex::new(r"[.*+?^${}()|[\]\\]").unwrap();
-    re.replace_all(s, "\\$0").to_string()
+// Finds the game folder directly below `location` that `cmd` refers to. `location` is matched
+// case-insensitively against `lowercase_cmd`, but the folder is returned with the casing used by
+// the command line.
+fn game_folder(cmd: &str, lowercase_cmd: &str, location: &str) -> Option<String> {
+    let lowercase_location = location.to_lowercase();
+    // Offsets only carry over if lowercasing kept every character's length, as it does for ASCII.
+    let start = if lowercase_cmd.len() == cmd.len() {
+        lowercase_cmd.find(&lowercase_location)?
+    } else {
+        cmd.to_ascii_lowercase().find(&location.to_ascii_lowercase())?
+    } + location.len();
+    let rest = cmd.get(start..)?.strip_prefix('/')?;
+    let name = &rest[..rest.find('/')?];
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || "-_ &".contains(c)) {
+        return None;
+    }
+    Some(format!("{}/{}", location, name))
+}
+
+fn read_receipt(path: &Path) -> Result<GameReceipt, DetectError> {
+    let game_receipt = read(path).map_err(|e| DetectError::io(path, e))?;
+    let mut unpacked_receipt = String::new();
+    let mut decoder = flate2::read::GzDecoder::new(game_receipt.as_slice());
+    decoder.read_to_string(&mut unpacked_receipt).map_err(|e| DetectError::parse(path, e))?;
+    serde_json::from_str(&unpacked_receipt).map_err(|e| DetectError::parse(path, e))
+}
+
+pub struct ItchIoDetector;
+
+impl Detector for ItchIoDetector {
+    fn name(&self) -> &str {
+        "itchio"
+    }
+
+    fn detect(&self, context: &DetectContext) -> Result<Vec<Detection>, DetectError> {
+        detect_games(context)
+    }
+
+    fn data_files(&self, context: &DetectContext) -> Vec<PathBuf> {
+        let mut files = vec![];
+        if let Ok(itch_dir) = context.app_data_dir().map(|dir| dir.join("itch")) {
+            files.push(itch_dir.join("preferences.json"));
+        }
+        for location in install_locations(context).unwrap_or_default() {
+            let Ok(entries) = read_dir(context.path(&location)) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                files.push(Path::new(&location).join(entry.file_name()).join(".itch").join("receipt.json.gz"));
+            }
+        }
+        files
+    }
 }
 
-pub fn detect_game(processes: &Vec<procfs::process::Process>) -> Result<Option<DetectedGame>> {
+fn install_locations(context: &DetectContext) -> Result<Vec<String>, DetectError> {
     let mut install_locations: Vec<String> = vec![];
-    #[cfg(target_family = "windows")]
-    let preferences_path = data_local_dir().unwrap().join("itch").join("preferences.json");
-    #[cfg(target_os = "linux")]
-    let preferences_path = home_dir().unwrap().join(".config").join("itch").join("preferences.json");
-    #[cfg(target_os = "macos")]
-    let preferences_path = home_dir().unwrap().join("Library").join("Application Support").join("itch").join("preferences.json");
+    let itch_dir = context.app_data_dir()?.join("itch");
+    let preferences_path = context.path(itch_dir.join("preferences.json"));
 
     #[cfg(target_family = "windows")]
-    if exists("C://Games/Itch Games").unwrap_or(false) {
+    if context.path("C://Games/Itch Games").exists() {
         install_locations.push("C://Games/Itch Games".to_owned());
     }
-    #[cfg(target_os = "linux")]
-    if exists(home_dir().unwrap().join(".config").join("itch").join("apps")).unwrap_or(false) {
-        install_locations.push(home_dir().unwrap().join(".config").join("itch").join("apps").to_str().unwrap().to_owned());
-    }
-    #[cfg(target_os = "macos")]
-    if exists(home_dir().unwrap().join("Library").join("Application Support").join("itch").join("apps")).unwrap_or(false) {
-        install_locations.push(home_dir().unwrap().join("Library").join("Application Support").join("itch").join("apps").to_str().unwrap().to_owned());
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    if context.path(itch_dir.join("apps")).exists() {
+        install_locations.push(itch_dir.join("apps").to_string_lossy().into_owned());
     }
 
-    if exists(&preferences_path).unwrap_or(false) {
-        let preferences: PartialPreferences = serde_json::from_str(&read_to_string(&preferences_path)?)?;
+    if preferences_path.exists() {
+        let preferences = read_to_string(&preferences_path).map_err(|e| DetectError::io(&preferences_path, e))?;
+        let preferences: PartialPreferences = serde_json::from_str(&preferences).map_err(|e| DetectError::parse(&preferences_path, e))?;
         for location in preferences.install_locations {
-            if exists(&location).unwrap_or(false) {
+            if context.path(&location).exists() {
                 install_locations.push(location);
             }
         }
     }
+    Ok(install_locations)
+}
 
-    for location in install_locations {
-        for process in processes {
-            let cmd = process.cmdline().ok();
-            match cmd {
-                Some(cmd) => {
-                    let cmd = cmd.join(" ").to_lowercase();
-                    if cmd.contains(&location.to_lowercase()) {
-                        let re = Regex::new(&format!(r"({0}/[a-zA-z0-9\-\_ &]+)/", escape_str(&cmd))).unwrap();
-                        let game_base_folder = match re.captures(&cmd) {
-                            Some(captures) => Some(captures.get(1).unwrap().as_str().to_owned()),
-                            None => None,
-                        };
-
-                        if let Some(game_base_folder) = game_base_folder {
-                            let game_receipt = read_to_string(format!("{}/.itch/receipt.json.gz", game_base_folder))?;
-                            let mut unpacked_receipt = String::new();
-                            let mut decoder = flate2::read::GzDecoder::new(game_receipt.as_bytes());
-                            decoder.read_to_string(&mut unpacked_receipt)?;
-                            let game_receipt: GameReceipt = serde_json::from_str(&unpacked_receipt)?;
-
-                            return Ok(Some(DetectedGame::ItchIo {
-                                id: game_receipt.game.id,
-                                name: game_receipt.game.title,
-                                url: game_receipt.game.url,
-                                cover: game_receipt.game.cover_url.clone(),
-                                icon: game_receipt.game.cover_url.clone(),
-                                description: game_receipt.game.description,
-                                developers: vec![game_receipt.game.user.display_name.clone()],
-                                publishers: vec![game_receipt.game.user.display_name.clone()],
-                            }))
-                        }
-                    }
+pub fn detect_games(context: &DetectContext) -> Result<Vec<Detection>, DetectError> {
+    let mut detections = vec![];
+    let processes = context.processes();
+    for location in install_locations(context)? {
+        // Games started from the location, then games under Wine that only name it in their
+        // command line.
+        let lowercase_location = location.to_lowercase();
+        let mut candidates = processes.by_path_prefix(&location);
+        let wine_candidates: Vec<_> = processes.iter()
+            .filter(|process| process.lowercase_cmdline.contains(&lowercase_location))
+            .filter(|process| !candidates.iter().any(|candidate| candidate.info.pid == process.info.pid))
+            .collect();
+        candidates.extend(wine_candidates);
+        for process in candidates {
+            let exe = process.info.exe.as_deref().map(|exe| exe.to_string_lossy());
+            let folder = exe.and_then(|exe| game_folder(&exe, &exe.to_lowercase(), &location))
+                .or_else(|| game_folder(process.cmdline, process.lowercase_cmdline, &location));
+            if let Some(game_base_folder) = folder {
+                let receipt_path = context.path(Path::new(&game_base_folder).join(".itch").join("receipt.json.gz"));
+                if !receipt_path.exists() {
+                    continue;
                 }
-                None => continue,
+                let game_receipt = read_receipt(&receipt_path)?;
+
+                detections.push(Detection::new(DetectedGame::ItchIo {
+                    id: game_receipt.game.id,
+                    name: game_receipt.game.title,
+                    url: game_receipt.game.url,
+                    cover: game_receipt.game.cover_url.clone(),
+                    icon: game_receipt.game.cover_url.clone(),
+                    description: game_receipt.game.description,
+                    developers: vec![game_receipt.game.user.display_name.clone()],
+                    publishers: vec![game_receipt.game.user.display_name.clone()],
+                }, Some(process.info.clone())));
             }
         }
     }
-    Ok(None)
+    Ok(detections)
 }
 
 #[cfg(feature = "http")]
-pub(crate) async fn fetch_info(detected: &DetectedGame) -> Result<GameInfo> {
+pub(crate) async fn fetch_info(detected: &DetectedGame) -> Result<GameInfo, FetchError> {
     match detected {
         DetectedGame::ItchIo { url, name, description, cover, icon, developers, publishers, .. } => {
             Ok(GameInfo {
@@ -133,7 +172,7 @@ pub(crate) async fn fetch_info(detected: &DetectedGame) -> Result<GameInfo> {
                 url: url.clone(),
             })
         }
-        _ => unreachable!(),
+        _ => Err(FetchError::Unsupported(detected.platform())),
     }
 }
 
@@ -141,6 +180,55 @@ pub(crate) async fn fetch_info(detected: &DetectedGame) -> Result<GameInfo> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_game_folder() {
+        let location = "/home/user/.config/itch/apps";
+        let game_folder = |cmd: &str| game_folder(cmd, &cmd.to_lowercase(), location);
+        assert_eq!(
+            game_folder("/home/user/.config/itch/apps/Ignited Entry/IgnitedEntry.x86_64 --flag"),
+            Some("/home/user/.config/itch/apps/Ignited Entry".to_owned())
+        );
+        assert_eq!(game_folder("/HOME/USER/.config/itch/apps/game/run"), Some("/home/user/.config/itch/apps/game".to_owned()));
+        assert_eq!(game_folder("/home/user/.config/itch/apps"), None);
+        assert_eq!(game_folder("/home/user/.config/itch/apps/game"), None);
+        assert_eq!(game_folder("/usr/bin/itch"), None);
+        // "İ" lowercases to three bytes, so the offsets in the lowercase command line are off by one.
+        assert_eq!(game_folder("İ /home/user/.config/itch/apps/game/run"), Some("/home/user/.config/itch/apps/game".to_owned()));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_detect_games() {
+        use std::io::Write;
+
+        let home = std::env::temp_dir().join(format!("game-detect-itchio-{}", std::process::id()));
+        let apps = home.join(".config/itch/apps");
+        let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        encoder.write_all(br#"{"game":{"id":1,"title":"Game","url":"https://dev.itch.io/game","coverUrl":"cover","shortText":"","user":{"displayName":"dev"}}}"#).unwrap();
+        let receipt = encoder.finish().unwrap();
+        for game in ["Game", "Wine Game"] {
+            std::fs::create_dir_all(apps.join(game).join(".itch")).unwrap();
+            std::fs::write(apps.join(game).join(".itch/receipt.json.gz"), &receipt).unwrap();
+        }
+        let process = |pid: i32, exe: &str, cmdline: &[&str]| crate::ProcessInfo {
+            pid,
+            exe: Some(PathBuf::from(exe)),
+            cmdline: cmdline.iter().map(|arg| arg.to_string()).collect(),
+            ..Default::default()
+        };
+        let native = apps.join("Game/game.x86_64");
+        let wine = format!("{}/Wine Game/game.exe", apps.display());
+        let context = DetectContext::new(vec![
+            process(1, "/usr/bin/bash", &["bash"]),
+            process(2, native.to_str().unwrap(), &["./game.x86_64"]),
+            process(3, "/usr/bin/wine64-preloader", &[&wine]),
+            process(4, "/usr/bin/itch", &["itch"]),
+        ], Some(home.clone()));
+        let pids: Vec<_> = detect_games(&context).unwrap().iter().map(|detection| detection.pid()).collect();
+        assert_eq!(pids, vec![Some(2), Some(3)]);
+        std::fs::remove_dir_all(&home).unwrap();
+    }
+
     #[actix_rt::test]
     #[cfg(feature = "http")]
     async fn test_fetch_info() {
@@ -1,49 +1,74 @@
 #[cfg(feature = "http")]
-use crate::{GameInfo, GamePlatform};
-use std::fs::exists;
-use anyhow::Result;
-use home::home_dir;
+use crate::{FetchError, GameInfo, GamePlatform};
+use std::path::PathBuf;
 
-use crate::DetectedGame;
+use crate::{DetectContext, DetectError, DetectedGame, Detection, Detector};
 
 const SQL: &str = "SELECT * FROM games";
 
-pub fn detect_game(processes: &Vec<procfs::process::Process>) -> Result<Option<DetectedGame>> {
-    let db_path = home_dir().unwrap().join(".local/share/lutris/pga.db");
-    if !exists(&db_path)? {
-        return Ok(None);
+pub struct LutrisDetector;
+
+impl Detector for LutrisDetector {
+    fn name(&self) -> &str {
+        "lutris"
+    }
+
+    fn detect(&self, context: &DetectContext) -> Result<Vec<Detection>, DetectError> {
+        detect_games(context)
     }
-    let db = sqlite::open(&db_path)?;
-    let cursor = db.prepare(SQL)?;
+
+    fn data_files(&self, context: &DetectContext) -> Vec<PathBuf> {
+        db_path(context).into_iter().collect()
+    }
+}
+
+fn db_path(context: &DetectContext) -> Result<PathBuf, DetectError> {
+    Ok(context.home()?.join(".local/share/lutris/pga.db"))
+}
+
+pub fn detect_games(context: &DetectContext) -> Result<Vec<Detection>, DetectError> {
+    let mut detections = vec![];
+    let db_path = context.path(db_path(context)?);
+    if !db_path.exists() {
+        return Ok(detections);
+    }
+    let db = sqlite::open(&db_path).map_err(|e| DetectError::parse(&db_path, e))?;
+    let cursor = db.prepare(SQL).map_err(|e| DetectError::parse(&db_path, e))?;
     let rows = cursor.into_iter();
+    // Only processes started through lutris-wrapper can belong to a Lutris game.
+    let wrapped: Vec<_> = context.processes().iter()
+        .filter(|process| process.lowercase_cmdline.contains("lutris-wrapper"))
+        .collect();
+    if wrapped.is_empty() {
+        return Ok(detections);
+    }
     for row in rows {
-        let row = row?;
+        let row = row.map_err(|e| DetectError::parse(&db_path, e))?;
 
-        let id: i64 = row.read("id");
-        let slug: &str = row.read("slug");
-        let name: &str = row.read("name");
-        let directory: &str = row.read("directory");
+        let id: i64 = row.try_read("id").map_err(|e| DetectError::parse(&db_path, e))?;
+        // Games that were never installed have no directory (and sometimes no slug).
+        let (Ok(Some(slug)), Ok(Some(name)), Ok(Some(directory))) = (
+            row.try_read::<Option<&str>, _>("slug"),
+            row.try_read::<Option<&str>, _>("name"),
+            row.try_read::<Option<&str>, _>("directory"),
+        ) else {
+            continue;
+        };
 
-        for process in processes {
-            let cmd = process.cmdline().ok();
-            match cmd {
-                Some(cmd) => {
-                    let cmd = cmd.join(" ").to_lowercase();
-                    if cmd.contains("lutris-wrapper") && cmd.contains(&name.to_lowercase()) && cmd.contains(&directory) {
-                        return Ok(Some(DetectedGame::Lutris {
-                            id,
-                            slug: slug.to_string(),
-                            name: name.to_string(),
-                            cover: format!("https://lutris.net/games/banner/{0}.jpg", slug),
-                            icon: format!("https://lutris.net/games/icon/{0}.png", slug)
-                        }))
-                    }
-                }
-                None => continue,
+        let lowercase_name = name.to_lowercase();
+        for process in &wrapped {
+            if process.lowercase_cmdline.contains(&lowercase_name) && process.cmdline.contains(directory) {
+                detections.push(Detection::new(DetectedGame::Lutris {
+                    id,
+                    slug: slug.to_string(),
+                    name: name.to_string(),
+                    cover: format!("https://lutris.net/games/banner/{0}.jpg", slug),
+                    icon: format!("https://lutris.net/games/icon/{0}.png", slug)
+                }, Some(process.info.clone())));
             }
         }
     }
-    Ok(None)
+    Ok(detections)
 }
 
 #[cfg(feature = "http")]
@@ -59,7 +84,7 @@ mod http {
 }
 
 #[cfg(feature = "http")]
-pub(crate) async fn fetch_info(detected: &DetectedGame) -> Result<GameInfo> {
+pub(crate) async fn fetch_info(detected: &DetectedGame) -> Result<GameInfo, FetchError> {
     match detected {
         DetectedGame::Lutris { slug, name, cover, icon, .. } => {
             let client = reqwest::Client::new();
@@ -80,10 +105,10 @@ pub(crate) async fn fetch_info(detected: &DetectedGame) -> Result<GameInfo> {
                     url: format!("https://lutris.net/games/{}", slug)
                 })
             } else {
-                Err(anyhow::anyhow!("Failed to fetch game info"))
+                Err(FetchError::Status(response.status()))
             }
         },
-        _ => unreachable!()
+        _ => Err(FetchError::Unsupported(detected.platform()))
     }
 }
 
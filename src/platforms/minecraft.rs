@@ -1,41 +1,47 @@
 #[cfg(feature = "http")]
-use crate::{GameInfo, GamePlatform};
-use crate::DetectedGame;
-use anyhow::Result;
+use crate::{FetchError, GameInfo, GamePlatform};
+use crate::{DetectContext, DetectError, DetectedGame, Detection, Detector};
 
-pub fn detect_game(processes: &Vec<procfs::process::Process>) -> Result<Option<DetectedGame>> {
-    for process in processes {
-        let cmd = process.cmdline().ok();
-        match cmd {
-            Some(cmd) => {
-                let cmd = cmd.join(" ").to_lowercase();
-                if cmd.contains("minecraft") {
-                    if cmd.contains("legends") {
-                        return Ok(Some(DetectedGame::MinecraftLegends {
-                            cover: "https://www.minecraft.net/content/dam/minecraft/home/home-hero-1200x600.jpg".to_owned(),
-                            icon: "https://www.minecraft.net/etc.clientlibs/minecraft/clientlibs/main/resources/favicon-96x96.png".to_owned()
-                        }));
-                    } else if cmd.contains("dungeons") {
-                        return Ok(Some(DetectedGame::MinecraftDungeons {
-                            cover: "https://www.minecraft.net/content/dam/minecraft/home/home-hero-1200x600.jpg".to_owned(),
-                            icon: "https://www.minecraft.net/etc.clientlibs/minecraft/clientlibs/main/resources/favicon-96x96.png".to_owned()
-                        }));
-                    } else {
-                        return Ok(Some(DetectedGame::Minecraft {
-                            cover: "https://www.minecraft.net/content/dam/minecraft/home/home-hero-1200x600.jpg".to_owned(),
-                            icon: "https://www.minecraft.net/etc.clientlibs/minecraft/clientlibs/main/resources/favicon-96x96.png".to_owned()
-                        }));
-                    }
-                }
+pub struct MinecraftDetector;
+
+impl Detector for MinecraftDetector {
+    fn name(&self) -> &str {
+        "minecraft"
+    }
+
+    fn detect(&self, context: &DetectContext) -> Result<Vec<Detection>, DetectError> {
+        detect_games(context)
+    }
+}
+
+pub fn detect_games(context: &DetectContext) -> Result<Vec<Detection>, DetectError> {
+    let mut detections = vec![];
+    for process in context.processes().iter() {
+        let cmd = process.lowercase_cmdline;
+        if cmd.contains("minecraft") {
+            if cmd.contains("legends") {
+                detections.push(Detection::new(DetectedGame::MinecraftLegends {
+                    cover: "https://www.minecraft.net/content/dam/minecraft/home/home-hero-1200x600.jpg".to_owned(),
+                    icon: "https://www.minecraft.net/etc.clientlibs/minecraft/clientlibs/main/resources/favicon-96x96.png".to_owned()
+                }, Some(process.info.clone())));
+            } else if cmd.contains("dungeons") {
+                detections.push(Detection::new(DetectedGame::MinecraftDungeons {
+                    cover: "https://www.minecraft.net/content/dam/minecraft/home/home-hero-1200x600.jpg".to_owned(),
+                    icon: "https://www.minecraft.net/etc.clientlibs/minecraft/clientlibs/main/resources/favicon-96x96.png".to_owned()
+                }, Some(process.info.clone())));
+            } else {
+                detections.push(Detection::new(DetectedGame::Minecraft {
+                    cover: "https://www.minecraft.net/content/dam/minecraft/home/home-hero-1200x600.jpg".to_owned(),
+                    icon: "https://www.minecraft.net/etc.clientlibs/minecraft/clientlibs/main/resources/favicon-96x96.png".to_owned()
+                }, Some(process.info.clone())));
             }
-            None => continue,
         }
     }
-    Ok(None)
+    Ok(detections)
 }
 
 #[cfg(feature = "http")]
-pub(crate) async fn fetch_info(detected: &DetectedGame) -> Result<GameInfo> {
+pub(crate) async fn fetch_info(detected: &DetectedGame) -> Result<GameInfo, FetchError> {
     match detected {
         DetectedGame::Minecraft { cover, icon } =>
             Ok(GameInfo {
@@ -76,6 +82,6 @@ pub(crate) async fn fetch_info(detected: &DetectedGame) -> Result<GameInfo> {
                 required_age: Some(10),
                 url: "https://store.steampowered.com/app/1672970".to_owned()
             }),
-        _ => unreachable!()
+        _ => Err(FetchError::Unsupported(detected.platform()))
     }
 }
\ No newline at end of file
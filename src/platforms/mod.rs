@@ -1,3 +1,4 @@
+pub mod custom;
 #[cfg(feature = "minecraft")]
 pub mod minecraft;
 #[cfg(feature = "roblox")]
@@ -9,4 +10,4 @@ pub mod lutris;
 #[cfg(feature = "itchio")]
 pub mod itchio;
 #[cfg(feature = "gamejolt")]
-pub mod gamejolt;
\ No newline at end of file
+pub mod gamejolt;
@@ -1,89 +1,95 @@
 #[cfg(feature = "http")]
-use crate::{GameInfo, GamePlatform};
+use crate::{FetchError, GameInfo, GamePlatform};
 #[cfg(target_family = "windows")]
 use std::fs::{read_dir, read_to_string};
-use anyhow::Result;
 use regex::Regex;
+#[cfg(target_family = "windows")]
+use std::path::PathBuf;
+
+use crate::{DetectContext, DetectError, DetectedGame, Detection, Detector};
+
+pub struct RobloxDetector;
+
+impl Detector for RobloxDetector {
+    fn name(&self) -> &str {
+        "roblox"
+    }
+
+    fn detect(&self, context: &DetectContext) -> Result<Vec<Detection>, DetectError> {
+        detect_games(context)
+    }
+
+    #[cfg(target_family = "windows")]
+    fn data_files(&self, context: &DetectContext) -> Vec<PathBuf> {
+        let Ok(log_dir) = context.app_data_dir().map(|dir| dir.join("Roblox").join("logs")) else {
+            return vec![];
+        };
+        newest_log(context, &log_dir).map(|log| log_dir.join(log)).into_iter().collect()
+    }
+}
+
+// Returns the file name of the newest log in `log_dir`.
+#[cfg(target_family = "windows")]
+fn newest_log(context: &DetectContext, log_dir: &std::path::Path) -> Option<std::ffi::OsString> {
+    read_dir(context.path(log_dir)).ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|extension| extension == "log"))
+        .max_by_key(|path| path.metadata().and_then(|metadata| metadata.modified()).ok())?
+        .file_name()
+        .map(|name| name.to_owned())
+}
+
+// Reads the place the client last joined from the newest log file, unless it has disconnected since.
+#[cfg(target_family = "windows")]
+fn place_id_from_logs(context: &DetectContext) -> Option<i64> {
+    let log_dir = context.app_data_dir().ok()?.join("Roblox").join("logs");
+    let newest_log = newest_log(context, &log_dir)?;
+    let logs = read_to_string(context.path(log_dir.join(newest_log))).ok()?;
+
+    for line in logs.lines().rev() {
+        if line.contains("[FLog::Network] Time to disconnect replication data:") {
+            return None;
+        }
+        if let Some((_, place_id)) = line.split_once("Report game_join_loadtime: placeid:") {
+            let digits = place_id.trim_start().split(|c: char| !c.is_ascii_digit()).next()?;
+            return digits.parse::<i64>().ok();
+        }
+    }
+    None
+}
+
+pub fn detect_games(context: &DetectContext) -> Result<Vec<Detection>, DetectError> {
+    let mut detections = vec![];
+    // The command line is lowercased before matching.
+    let re = Regex::new(r"placeid=(\d+)").expect("valid regex");
+    for process in context.processes().iter() {
+        let cmd = process.lowercase_cmdline;
+        if cmd.contains("robloxplayerbeta") {
+            #[cfg(target_family = "windows")]
+            if let Some(place_id) = place_id_from_logs(context) {
+                detections.push(Detection::new(DetectedGame::Roblox {
+                    id: place_id,
+                    url: format!("https://roblox.com/games/{0}", place_id)
+                }, Some(process.info.clone())));
+                continue;
+            }
 
-use crate::DetectedGame;
-
-pub fn detect_game(processes: &Vec<procfs::process::Process>) -> Result<Option<DetectedGame>> {
-    for process in processes {
-        let cmd = process.cmdline().ok();
-        match cmd {
-            Some(cmd) => {
-                let cmd = cmd.join(" ").to_lowercase();
-                if cmd.contains("robloxplayerbeta") {
-                    #[cfg(target_family = "windows")]
-                    {
-                        let log_dir = dirs::data_local_dir().unwrap().join("Roblox\\logs");
-                        for entry in read_dir(log_dir).unwrap() {
-                            let entry = entry.unwrap();
-                            let path = entry.path();
-                            if path.is_file() {
-                                if path.extension().unwrap() == "log" {
-                                    let logs = read_to_string(path).unwrap();
-                                    let log = logs.split("\n").collect::<Vec<&str>>();
-                                    let logs_reversed = log.iter().rev().collect::<Vec<&&str>>();
-
-                                    let mut place_id = None;
-                                    let mut disconnect_found = false;
-
-                                    if logs_reversed.len() > 0 {
-                                        for line in logs_reversed {
-                                            let line = *line;
-
-                                            if place_id.is_some() || disconnect_found {
-                                                return Ok(Some(DetectedGame::Roblox {
-                                                    id: place_id.unwrap(),
-                                                    url: format!("https://roblox.com/games/{0}", place_id.unwrap())
-                                                }))
-                                            }
-
-                                            if line.contains("[FLog::Network] Time to disconnect replication data:") {
-                                                disconnect_found = true;
-                                            }
-
-                                            if line.contains("Report game_join_loadtime: placeid:") {
-                                                let split = line.split("placeid:").collect::<Vec<&str>>();
-                                                if split.len() != 2 {
-                                                    continue;
-                                                }
-                                                let place_id_line = split[1].trim().parse::<i64>();
-                                                match place_id_line {
-                                                    Ok(place_id_line) => {
-                                                        place_id = Some(place_id_line);
-                                                    }
-                                                    Err(_) => continue,
-                                                }
-                                            }
-                                        }
-                                    } else {
-                                        break;
-                                    }
-                                }
-                            }
-                        }
-                    }
-
-                    let re = Regex::new(r"placeId=(\d+)").unwrap();
-                    let caps = re.captures(&cmd);
-                    match caps {
-                        Some(caps) => {
-                            let place_id = caps.get(1).unwrap().as_str().parse::<i64>().unwrap();
-                            return Ok(Some(DetectedGame::Roblox {
-                                id: place_id,
-                                url: format!("https://roblox.com/games/{0}", place_id)
-                            }))
-                        }
-                        None => continue,
-                    }
+            let place_id = re.captures(cmd)
+                .and_then(|caps| caps.get(1))
+                .and_then(|place_id| place_id.as_str().parse::<i64>().ok());
+            match place_id {
+                Some(place_id) => {
+                    detections.push(Detection::new(DetectedGame::Roblox {
+                        id: place_id,
+                        url: format!("https://roblox.com/games/{0}", place_id)
+                    }, Some(process.info.clone())));
                 }
+                None => continue,
             }
-            None => continue,
         }
     }
-    Ok(None)
+    Ok(detections)
 }
 
 #[cfg(feature = "http")]
@@ -143,29 +149,29 @@ mod http {
 }
 
 #[cfg(feature = "http")]
-pub(crate) async fn fetch_info(detected: &DetectedGame) -> Result<GameInfo> {
+pub(crate) async fn fetch_info(detected: &DetectedGame) -> Result<GameInfo, FetchError> {
     match detected {
         DetectedGame::Roblox { id, .. } => {
             let client = reqwest::Client::new();
             let universe_response = client.get(format!("https://apis.roblox.com/universes/v1/places/{}/universe", id)).send().await?;
             
             if universe_response.status().is_success() {
-                let universe_response: http::UniverseResponse = serde_json::from_str(&universe_response.text().await?).unwrap();
+                let universe_response: http::UniverseResponse = serde_json::from_str(&universe_response.text().await?)?;
                 let universe_id = universe_response.universe_id;
 
                 let icon_response = client.get(format!("https://thumbnails.roblox.com/v1/games/icons?universeIds={}&size=50x50&format=png", universe_id)).send().await?;
-                let icon_response: http::IconResponse = serde_json::from_str(&icon_response.text().await?).unwrap();
-                let icon = icon_response.data[0].image_url.clone();
+                let icon_response: http::IconResponse = serde_json::from_str(&icon_response.text().await?)?;
+                let icon = icon_response.data.first().ok_or(FetchError::NotFound)?.image_url.clone();
 
                 let thumbnail_response = client.get(format!("https://thumbnails.roblox.com/v1/games/multiget/thumbnails?universeIds={}&size=768x432&format=png&countPerUniverse=1", universe_id)).send().await?;
-                let thumbnail_response: http::ThumbnailResponse = serde_json::from_str(&thumbnail_response.text().await?).unwrap();
-                let thumbnail = thumbnail_response.data.data.thumbnails[0].image_url.clone();
+                let thumbnail_response: http::ThumbnailResponse = serde_json::from_str(&thumbnail_response.text().await?)?;
+                let thumbnail = thumbnail_response.data.data.thumbnails.first().ok_or(FetchError::NotFound)?.image_url.clone();
 
                 let game_response = client.get(format!("https://games.roblox.com/v1/games?universeIds={}", universe_id)).send().await?;
-                let game_response: http::GameResponse = serde_json::from_str(&game_response.text().await?).unwrap();
-                let game = game_response.data[0].clone();
+                let game_response: http::GameResponse = serde_json::from_str(&game_response.text().await?)?;
+                let game = game_response.data.first().ok_or(FetchError::NotFound)?.clone();
 
-                return Ok(GameInfo {
+                Ok(GameInfo {
                     app_id: None,
                     name: game.name,
                     description: game.description,
@@ -176,12 +182,12 @@ pub(crate) async fn fetch_info(detected: &DetectedGame) -> Result<GameInfo> {
                     via_platform: GamePlatform::Roblox,
                     required_age: None,
                     url: format!("https://roblox.com/games/{}", game.root_place_id)
-                });
+                })
             } else {
-                return Err(anyhow::anyhow!("Failed to fetch game info"));
+                Err(FetchError::Status(universe_response.status()))
             }
         }
-        _ => unreachable!(),
+        _ => Err(FetchError::Unsupported(detected.platform())),
     }
 }
 
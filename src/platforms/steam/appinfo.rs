@@ -0,0 +1,603 @@
+use std::collections::HashMap;
+use std::fs::metadata;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, SystemTime};
+
+use crate::{DetectContext, DetectError, GameInfo, GamePlatform};
+use super::binary_vdf::{self, Reader, Value};
+use super::library::installed_app;
+use super::steam_roots;
+
+const V26: u32 = 0x07564426;
+const V27: u32 = 0x07564427;
+const V28: u32 = 0x07564428;
+const V29: u32 = 0x07564429;
+
+/// Metadata the Steam client caches in `appcache/appinfo.vdf`, available without a network
+/// connection.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AppInfo {
+    pub app_id: i64,
+    pub name: String,
+    /// The raw `type`, such as `Game`, `DLC`, `Tool` or `Application`.
+    pub app_type: String,
+    pub developers: Vec<String>,
+    pub publishers: Vec<String>,
+    pub release_date: Option<SystemTime>,
+    pub assets: AppAssets,
+    pub change_number: u32,
+    pub last_updated: Option<SystemTime>,
+}
+
+/// Hashes of the images Steam serves from
+/// `https://cdn.cloudflare.steamstatic.com/steamcommunity/public/images/apps/<app id>/<hash>.<ext>`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AppAssets {
+    pub icon: Option<String>,
+    pub client_icon: Option<String>,
+    pub client_tga: Option<String>,
+    pub linux_client_icon: Option<String>,
+    pub logo: Option<String>,
+    pub logo_small: Option<String>,
+}
+
+/// What kind of app an app id belongs to, from the `type` Steam gives it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum AppType {
+    Game,
+    Demo,
+    Mod,
+    Dlc,
+    /// Proton, the Steam Linux Runtime, redistributables, SteamVR and other tools.
+    Tool,
+    Application,
+    Music,
+    Video,
+    Config,
+    /// A type this crate doesn't know about.
+    Other(String),
+    #[default]
+    Unknown,
+}
+
+impl AppType {
+    /// Whether the app is something the user plays. Unknown and unrecognized types count as games,
+    /// so nothing is hidden on a guess.
+    pub fn is_game(&self) -> bool {
+        !matches!(self, AppType::Dlc | AppType::Tool | AppType::Application | AppType::Music | AppType::Video | AppType::Config)
+    }
+}
+
+impl From<&str> for AppType {
+    fn from(app_type: &str) -> Self {
+        match app_type.to_ascii_lowercase().as_str() {
+            "" => AppType::Unknown,
+            "game" => AppType::Game,
+            "demo" => AppType::Demo,
+            "mod" => AppType::Mod,
+            "dlc" => AppType::Dlc,
+            "tool" => AppType::Tool,
+            "application" => AppType::Application,
+            "music" => AppType::Music,
+            "video" | "series" | "episode" => AppType::Video,
+            "config" => AppType::Config,
+            _ => AppType::Other(app_type.to_owned()),
+        }
+    }
+}
+
+// Proton, Steam Linux Runtime, Steamworks Common Redistributables and SteamVR releases, recognized
+// without reading appinfo.vdf.
+const KNOWN_TOOLS: [i64; 19] = [
+    228980, 250820, 930400, 961940, 1054830, 1070560, 1113280, 1161040, 1245040, 1391110,
+    1420170, 1493710, 1580130, 1628350, 1826330, 1887720, 2180100, 2348590, 2805730,
+];
+
+impl AppInfo {
+    pub fn kind(&self) -> AppType {
+        AppType::from(self.app_type.as_str())
+    }
+
+    /// The cached metadata as [`GameInfo`]. The cache has no description or age rating.
+    pub fn to_game_info(&self) -> GameInfo {
+        GameInfo {
+            app_id: Some(self.app_id),
+            via_platform: GamePlatform::Steam,
+            name: self.name.clone(),
+            description: String::new(),
+            cover: format!("https://cdn.cloudflare.steamstatic.com/steam/apps/{0}/library_hero.jpg", self.app_id),
+            icon: format!("https://cdn.cloudflare.steamstatic.com/steam/apps/{0}/hero_capsule.jpg", self.app_id),
+            developers: self.developers.clone(),
+            publishers: self.publishers.clone(),
+            required_age: None,
+            url: format!("https://store.steampowered.com/app/{0}", self.app_id),
+        }
+    }
+
+    fn from_data(app_id: u32, last_updated: u32, change_number: u32, data: &Value) -> Self {
+        let root = data.get("appinfo").unwrap_or(data);
+        let common = root.get("common");
+        let string = |key: &str| common.and_then(|common| common.get(key)).and_then(Value::as_str).map(str::to_owned);
+        let release_date = ["steam_release_date", "original_release_date"].iter()
+            .find_map(|key| common?.get(key)?.as_int().filter(|seconds| *seconds > 0));
+        Self {
+            app_id: app_id as i64,
+            name: string("name").unwrap_or_default(),
+            app_type: string("type").unwrap_or_default(),
+            developers: companies(root, "developer"),
+            publishers: companies(root, "publisher"),
+            release_date: release_date.map(|seconds| SystemTime::UNIX_EPOCH + Duration::from_secs(seconds as u64)),
+            assets: AppAssets {
+                icon: string("icon"),
+                client_icon: string("clienticon"),
+                client_tga: string("clienttga"),
+                linux_client_icon: string("linuxclienticon"),
+                logo: string("logo"),
+                logo_small: string("logo_small"),
+            },
+            change_number,
+            last_updated: Some(last_updated).filter(|seconds| *seconds != 0)
+                .map(|seconds| SystemTime::UNIX_EPOCH + Duration::from_secs(seconds as u64)),
+        }
+    }
+}
+
+// Newer entries list companies in `common/associations`, older ones in `extended`.
+fn companies(root: &Value, kind: &str) -> Vec<String> {
+    let associations: Vec<String> = root.lookup(&["common", "associations"])
+        .map(|associations| associations.entries().iter()
+            .filter(|(_, association)| association.get("type").and_then(Value::as_str) == Some(kind))
+            .filter_map(|(_, association)| association.get("name")?.as_str())
+            .map(str::to_owned)
+            .collect())
+        .unwrap_or_default();
+    if !associations.is_empty() {
+        return associations;
+    }
+    root.lookup(&["extended", kind]).and_then(Value::as_str)
+        .filter(|name| !name.is_empty())
+        .map(|name| vec![name.to_owned()])
+        .unwrap_or_default()
+}
+
+// The cache of the first install that has one.
+fn appinfo_path(context: &DetectContext) -> Result<Option<PathBuf>, DetectError> {
+    Ok(steam_roots(context)?.into_iter()
+        .map(|root| root.join("appcache").join("appinfo.vdf"))
+        .find(|path| context.path(path).exists()))
+}
+
+/// The cached metadata for `app_id`, or `None` if Steam or the app isn't in the cache.
+pub fn app_info(context: &DetectContext, app_id: i64) -> Result<Option<AppInfo>, DetectError> {
+    Ok(apps_info(context, &[app_id])?.pop())
+}
+
+/// The cached metadata for each of `app_ids` that is in the cache, reading the cache only once.
+pub(crate) fn apps_info(context: &DetectContext, app_ids: &[i64]) -> Result<Vec<AppInfo>, DetectError> {
+    let app_ids: Vec<u32> = app_ids.iter()
+        .filter(|app_id| **app_id > 0 && **app_id <= u32::MAX as i64)
+        .map(|app_id| *app_id as u32)
+        .collect();
+    if app_ids.is_empty() {
+        return Ok(vec![]);
+    }
+    read_apps(context, Some(&app_ids))
+}
+
+/// How Steam classifies an app, from the `type` in its cached metadata, or for a few well-known
+/// tools from their app id or installed name. `Unknown` when none of them says.
+pub fn app_type(context: &DetectContext, app_id: i64) -> Result<AppType, DetectError> {
+    Ok(app_types(context, &[app_id])?.pop().map(|(_, app_type)| app_type).unwrap_or_default())
+}
+
+/// Tools are recognized from their id or manifest first, and types read from the cache are
+/// remembered until it changes, as a watcher asks about the same running apps on every poll.
+pub(crate) fn app_types(context: &DetectContext, app_ids: &[i64]) -> Result<Vec<(i64, AppType)>, DetectError> {
+    let mut types: Vec<(i64, Option<AppType>)> = vec![];
+    for app_id in app_ids {
+        let is_tool = KNOWN_TOOLS.contains(app_id)
+            || installed_app(context, *app_id)?.is_some_and(|app| is_tool_name(&app.name));
+        types.push((*app_id, is_tool.then_some(AppType::Tool)));
+    }
+    let unresolved: Vec<i64> = types.iter().filter(|(_, app_type)| app_type.is_none()).map(|(app_id, _)| *app_id).collect();
+    if !unresolved.is_empty() {
+        let cached = cached_types(context, &unresolved)?;
+        for (app_id, app_type) in &mut types {
+            if app_type.is_none() {
+                *app_type = cached.iter().find(|(id, _)| id == app_id).map(|(_, app_type)| app_type.clone());
+            }
+        }
+    }
+    Ok(types.into_iter().map(|(app_id, app_type)| (app_id, app_type.unwrap_or_default())).collect())
+}
+
+// Matches the names of Proton releases and Valve's runtimes rather than every "Proton ..." title,
+// so games such as "Proton Pulse" aren't taken for tools.
+fn is_tool_name(name: &str) -> bool {
+    name.starts_with("Steam Linux Runtime")
+        || name == "Steamworks Common Redistributables"
+        || name == "SteamVR"
+        || name.strip_prefix("Proton ").is_some_and(|rest| {
+            rest.starts_with(|c: char| c.is_ascii_digit())
+                || ["Experimental", "- Experimental", "Hotfix", "Next", "EasyAntiCheat Runtime", "BattlEye Runtime"].contains(&rest)
+        })
+}
+
+struct TypeCache {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    len: u64,
+    /// `Unknown` for apps the cache doesn't have.
+    types: HashMap<i64, AppType>,
+}
+
+static TYPE_CACHE: Mutex<Option<TypeCache>> = Mutex::new(None);
+
+fn cached_types(context: &DetectContext, app_ids: &[i64]) -> Result<Vec<(i64, AppType)>, DetectError> {
+    let Some(path) = appinfo_path(context)? else {
+        return Ok(vec![]);
+    };
+    let path = context.path(path);
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let metadata = metadata(&path).map_err(|e| DetectError::io(&path, e))?;
+    let (modified, len) = (metadata.modified().ok(), metadata.len());
+    let mut cache = TYPE_CACHE.lock().unwrap_or_else(PoisonError::into_inner);
+    let cache = match cache.as_mut() {
+        Some(cache) if cache.path == path && cache.modified == modified && cache.len == len => cache,
+        _ => cache.insert(TypeCache { path: path.clone(), modified, len, types: HashMap::new() }),
+    };
+    let missing: Vec<i64> = app_ids.iter().filter(|app_id| !cache.types.contains_key(app_id)).copied().collect();
+    if !missing.is_empty() {
+        let apps = apps_info(context, &missing)?;
+        for app_id in missing {
+            let app_type = apps.iter().find(|app| app.app_id == app_id).map(AppInfo::kind).unwrap_or_default();
+            cache.types.insert(app_id, app_type);
+        }
+    }
+    Ok(app_ids.iter().map(|app_id| (*app_id, cache.types[app_id].clone())).collect())
+}
+
+/// Every app in the metadata cache, in file order.
+pub fn all_app_info(context: &DetectContext) -> Result<Vec<AppInfo>, DetectError> {
+    read_apps(context, None)
+}
+
+fn read_apps(context: &DetectContext, only: Option<&[u32]>) -> Result<Vec<AppInfo>, DetectError> {
+    let Some(path) = appinfo_path(context)? else {
+        return Ok(vec![]);
+    };
+    let path = context.path(path);
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let file = std::fs::File::open(&path).map_err(|e| DetectError::io(&path, e))?;
+    // The file is often over 100 MB, so only the apps asked for are read into memory. A small
+    // buffer keeps skipping over the others cheap.
+    parse(BufReader::with_capacity(4096, file), only).map_err(|e| DetectError::parse(&path, e))
+}
+
+// A buffered cursor over the file that knows its length, so sizes read from a corrupt file are
+// checked before anything is allocated for them.
+struct File<R> {
+    reader: BufReader<R>,
+    offset: u64,
+    len: u64,
+}
+
+impl<R: Read + Seek> File<R> {
+    fn new(mut reader: BufReader<R>) -> Result<Self, binary_vdf::Error> {
+        let len = reader.seek(SeekFrom::End(0)).and_then(|len| reader.rewind().map(|_| len))
+            .map_err(|error| binary_vdf::Error { offset: 0, message: error.to_string() })?;
+        Ok(Self { reader, offset: 0, len })
+    }
+
+    fn error(&self, message: impl Into<String>) -> binary_vdf::Error {
+        binary_vdf::Error { offset: self.offset as usize, message: message.into() }
+    }
+
+    fn check(&self, len: u64) -> Result<(), binary_vdf::Error> {
+        match self.offset.checked_add(len) {
+            Some(end) if end <= self.len => Ok(()),
+            _ => Err(self.error("unexpected end of file")),
+        }
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), binary_vdf::Error> {
+        self.check(buf.len() as u64)?;
+        self.reader.read_exact(buf).map_err(|error| self.error(error.to_string()))?;
+        self.offset += buf.len() as u64;
+        Ok(())
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<Vec<u8>, binary_vdf::Error> {
+        self.check(len as u64)?;
+        let mut bytes = vec![0; len];
+        self.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, binary_vdf::Error> {
+        let mut bytes = [0; 4];
+        self.read_exact(&mut bytes)?;
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn u64(&mut self) -> Result<u64, binary_vdf::Error> {
+        let mut bytes = [0; 8];
+        self.read_exact(&mut bytes)?;
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    // Skipping within the buffer keeps it, unlike seeking.
+    fn skip(&mut self, len: u64) -> Result<(), binary_vdf::Error> {
+        self.check(len)?;
+        self.reader.seek_relative(len as i64).map_err(|error| self.error(error.to_string()))?;
+        self.offset += len;
+        Ok(())
+    }
+
+    fn seek(&mut self, offset: u64) -> Result<(), binary_vdf::Error> {
+        if offset > self.len {
+            return Err(self.error("offset past the end of the file"));
+        }
+        self.reader.seek(SeekFrom::Start(offset)).map_err(|error| self.error(error.to_string()))?;
+        self.offset = offset;
+        Ok(())
+    }
+}
+
+/// Parses every version of the file since 2012 (v26 to v29). When `only` is set, other apps are
+/// skipped without being read, and parsing stops once all of them are found.
+fn parse<R: Read + Seek>(reader: BufReader<R>, only: Option<&[u32]>) -> Result<Vec<AppInfo>, binary_vdf::Error> {
+    let file = &mut File::new(reader)?;
+    let version = file.u32()?;
+    if ![V26, V27, V28, V29].contains(&version) {
+        return Err(binary_vdf::Error { offset: 0, message: format!("unsupported appinfo.vdf version {version:#010x}") });
+    }
+    let _universe = file.u32()?;
+    let mut keys = None;
+    if version == V29 {
+        // v29 stores key names once, in a table at the end of the file.
+        let string_table = file.u64()?;
+        let apps_start = file.offset;
+        file.seek(string_table)?;
+        let count = file.u32()?;
+        let table = file.bytes((file.len - file.offset) as usize)?;
+        let mut reader = Reader::new(&table);
+        let names = (0..count).map(|_| reader.cstring()).collect::<Result<Vec<_>, _>>()
+            .map_err(|error| binary_vdf::Error { offset: string_table as usize + 4 + error.offset, ..error })?;
+        keys = Some(names);
+        file.seek(apps_start)?;
+    }
+
+    let mut apps = vec![];
+    loop {
+        let app_id = file.u32()?;
+        if app_id == 0 {
+            break;
+        }
+        let size = file.u32()?;
+        if only.is_some_and(|only| !only.contains(&app_id)) {
+            file.skip(size as u64)?;
+            continue;
+        }
+        let start = file.offset as usize;
+        let entry = file.bytes(size as usize)?;
+        let (last_updated, change_number, data) = parse_entry(&entry, version, keys.as_deref())
+            .map_err(|error| binary_vdf::Error { offset: start + error.offset, ..error })?;
+        apps.push(AppInfo::from_data(app_id, last_updated, change_number, &data));
+        if only.is_some_and(|only| apps.len() == only.len()) {
+            break;
+        }
+    }
+    Ok(apps)
+}
+
+fn parse_entry(entry: &[u8], version: u32, keys: Option<&[String]>) -> Result<(u32, u32, Value), binary_vdf::Error> {
+    let mut reader = Reader::new(entry);
+    if let Some(keys) = keys {
+        reader = reader.with_keys(keys);
+    }
+    let _info_state = reader.u32()?;
+    let last_updated = reader.u32()?;
+    let _access_token = reader.u64()?;
+    let _text_sha1 = reader.bytes(20)?;
+    let change_number = reader.u32()?;
+    if version >= V28 {
+        let _binary_sha1 = reader.bytes(20)?;
+    }
+    let data = if version == V26 { sections(&mut reader)? } else { reader.table()? };
+    Ok((last_updated, change_number, data))
+}
+
+// v26 splits each app into numbered sections, each a table named after the section, such as
+// "common", ending with a zero section number.
+fn sections(reader: &mut Reader) -> Result<Value, binary_vdf::Error> {
+    let mut sections = vec![];
+    while reader.u8()? != 0 {
+        let _kind = reader.u8()?;
+        let name = reader.cstring()?;
+        sections.push((name, reader.table()?));
+        let _end = reader.u8()?;
+    }
+    Ok(Value::Table(sections))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(entries: Vec<(&str, Value)>) -> Value {
+        Value::Table(entries.into_iter().map(|(key, value)| (key.to_owned(), value)).collect())
+    }
+
+    fn string(value: &str) -> Value {
+        Value::String(value.to_owned())
+    }
+
+    fn apps() -> Vec<(u32, Value)> {
+        vec![
+            (620, table(vec![
+                ("appid", Value::Int(620)),
+                ("common", table(vec![
+                    ("name", string("Portal 2")),
+                    ("type", string("Game")),
+                    ("steam_release_date", Value::Int(1303171200)),
+                    ("clienticon", string("2e478fc6874d06ae5baf0d147f6f21203291aa02")),
+                    ("logo", string("d2a1119ddc202fab81d9b87048f495cbd6377502")),
+                    ("associations", table(vec![
+                        ("0", table(vec![("type", string("developer")), ("name", string("Valve"))])),
+                        ("1", table(vec![("type", string("publisher")), ("name", string("Valve"))])),
+                        ("2", table(vec![("type", string("franchise")), ("name", string("Portal"))])),
+                    ])),
+                ])),
+            ])),
+            (400, table(vec![
+                ("appid", Value::Int(400)),
+                ("common", table(vec![("name", string("Portal")), ("type", string("game"))])),
+                ("extended", table(vec![("developer", string("Valve")), ("publisher", string(""))])),
+            ])),
+        ]
+    }
+
+    fn file(version: u32, apps: &[(u32, Value)]) -> Vec<u8> {
+        let mut keys = vec![];
+        let mut out = vec![];
+        out.extend(version.to_le_bytes());
+        out.extend(1u32.to_le_bytes());
+        if version == V29 {
+            out.extend(0u64.to_le_bytes());
+        }
+        for (app_id, data) in apps {
+            let mut entry = vec![];
+            entry.extend(2u32.to_le_bytes());
+            entry.extend(1700000000u32.to_le_bytes());
+            entry.extend(0u64.to_le_bytes());
+            entry.extend([0; 20]);
+            entry.extend(12345u32.to_le_bytes());
+            if version >= V28 {
+                entry.extend([0; 20]);
+            }
+            if version == V26 {
+                for (index, (name, section)) in data.entries().iter().enumerate() {
+                    if let Value::Table(_) = section {
+                        entry.extend([index as u8 + 1, 0x00]);
+                        entry.extend(name.as_bytes());
+                        entry.push(0);
+                        entry.extend(binary_vdf::write(section, None));
+                        entry.push(0x08);
+                    }
+                }
+                entry.push(0);
+            } else {
+                let data = Value::Table(vec![("appinfo".to_owned(), data.clone())]);
+                entry.extend(binary_vdf::write(&data, (version == V29).then_some(&mut keys)));
+            }
+            out.extend(app_id.to_le_bytes());
+            out.extend((entry.len() as u32).to_le_bytes());
+            out.extend(entry);
+        }
+        out.extend(0u32.to_le_bytes());
+        if version == V29 {
+            let offset = out.len() as u64;
+            out[8..16].copy_from_slice(&offset.to_le_bytes());
+            out.extend((keys.len() as u32).to_le_bytes());
+            for key in keys {
+                out.extend(key.as_bytes());
+                out.push(0);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_app_type() {
+        assert_eq!(AppType::from("Game"), AppType::Game);
+        assert_eq!(AppType::from("DLC"), AppType::Dlc);
+        assert_eq!(AppType::from(""), AppType::Unknown);
+        assert_eq!(AppType::from("Beta"), AppType::Other("Beta".to_owned()));
+        assert!(AppType::Demo.is_game());
+        assert!(AppType::Unknown.is_game());
+        assert!(!AppType::Tool.is_game());
+        assert!(!AppType::Application.is_game());
+        assert!(is_tool_name("Proton 9.0"));
+        assert!(is_tool_name("Proton - Experimental"));
+        assert!(is_tool_name("Steam Linux Runtime 3.0 (sniper)"));
+        assert!(!is_tool_name("Proton Pulse"));
+    }
+
+    #[test]
+    fn test_parse() {
+        let parse = |data: &[u8], only: Option<&[u32]>| parse(BufReader::new(std::io::Cursor::new(data)), only);
+        for version in [V26, V27, V28, V29] {
+            let data = file(version, &apps());
+            let apps = parse(&data, None).unwrap();
+            assert_eq!(apps.len(), 2, "version {version:#x}");
+            assert_eq!(apps[0], AppInfo {
+                app_id: 620,
+                name: "Portal 2".to_owned(),
+                app_type: "Game".to_owned(),
+                developers: vec!["Valve".to_owned()],
+                publishers: vec!["Valve".to_owned()],
+                release_date: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1303171200)),
+                assets: AppAssets {
+                    client_icon: Some("2e478fc6874d06ae5baf0d147f6f21203291aa02".to_owned()),
+                    logo: Some("d2a1119ddc202fab81d9b87048f495cbd6377502".to_owned()),
+                    ..Default::default()
+                },
+                change_number: 12345,
+                last_updated: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1700000000)),
+            }, "version {version:#x}");
+            assert_eq!(apps[1].developers, vec!["Valve"]);
+            assert!(apps[1].publishers.is_empty());
+            assert_eq!(apps[1].release_date, None);
+
+            let apps = parse(&data, Some(&[400])).unwrap();
+            assert_eq!(apps.iter().map(|app| app.app_id).collect::<Vec<_>>(), vec![400]);
+            assert!(parse(&data, Some(&[1])).unwrap().is_empty());
+        }
+
+        let error = parse(&0x07564425u32.to_le_bytes(), None).unwrap_err();
+        assert_eq!(error.message, "unsupported appinfo.vdf version 0x07564425");
+        let data = file(V28, &apps());
+        assert!(parse(&data[..data.len() - 10], None).is_err());
+
+        // A corrupt size is rejected before anything is allocated for it.
+        let data = [V28, 1, 620, u32::MAX].iter().flat_map(|value| value.to_le_bytes()).collect::<Vec<_>>();
+        assert_eq!(parse(&data, None).unwrap_err().message, "unexpected end of file");
+        assert_eq!(parse(&data, Some(&[400])).unwrap_err().message, "unexpected end of file");
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_app_info() {
+        let home = std::env::temp_dir().join(format!("game-detect-steam-appinfo-{}", std::process::id()));
+        let root = home.join(".local/share/Steam");
+        std::fs::create_dir_all(root.join("steamapps")).unwrap();
+        std::fs::create_dir_all(root.join("appcache")).unwrap();
+        let context = DetectContext::new(vec![], Some(home.clone()));
+        assert_eq!(app_info(&context, 620).unwrap(), None);
+
+        std::fs::write(root.join("appcache/appinfo.vdf"), file(V29, &apps())).unwrap();
+        let info = app_info(&context, 620).unwrap().unwrap().to_game_info();
+        assert_eq!(info.name, "Portal 2");
+        assert_eq!(info.app_id, Some(620));
+        assert_eq!(info.url, "https://store.steampowered.com/app/620");
+        assert_eq!(app_info(&context, 0).unwrap(), None);
+        assert_eq!(all_app_info(&context).unwrap().len(), 2);
+        assert_eq!(app_type(&context, 620).unwrap(), AppType::Game);
+        assert_eq!(app_type(&context, 1493710).unwrap(), AppType::Tool);
+        assert_eq!(app_type(&context, 440).unwrap(), AppType::Unknown);
+
+        std::fs::write(root.join("steamapps/appmanifest_3658110.acf"), r#""AppState" { "appid" "3658110" "name" "Proton 10.0" "installdir" "Proton 10.0" }"#).unwrap();
+        assert_eq!(app_type(&context, 3658110).unwrap(), AppType::Tool);
+        std::fs::remove_dir_all(&home).unwrap();
+    }
+}
@@ -0,0 +1,263 @@
+//! Valve's binary KeyValues format, used by `appinfo.vdf` and `shortcuts.vdf`.
+
+use std::fmt;
+
+const TABLE: u8 = 0x00;
+const STRING: u8 = 0x01;
+const INT32: u8 = 0x02;
+const FLOAT32: u8 = 0x03;
+const POINTER: u8 = 0x04;
+const WIDE_STRING: u8 = 0x05;
+const COLOR: u8 = 0x06;
+const UINT64: u8 = 0x07;
+const END: u8 = 0x08;
+const INT64: u8 = 0x0A;
+const ALTERNATE_END: u8 = 0x0B;
+
+// Real files nest a handful of levels; this stops crafted ones from overflowing the stack.
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// Entries in file order. Lists such as shortcuts are tables keyed "0", "1", ...
+    Table(Vec<(String, Value)>),
+    String(String),
+    Int(i64),
+    Float(f32),
+}
+
+impl Value {
+    // Keys ignore case, as with text VDF.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Table(entries) => entries.iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(key))
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn lookup(&self, path: &[&str]) -> Option<&Value> {
+        path.iter().try_fold(self, |value, key| value.get(key))
+    }
+
+    pub fn entries(&self) -> &[(String, Value)] {
+        match self {
+            Value::Table(entries) => entries,
+            _ => &[],
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Numbers are sometimes stored as strings, so both are accepted.
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Value::Int(value) => Some(*value),
+            Value::String(value) => value.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub offset: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.message, self.offset)
+    }
+}
+
+/// A cursor over binary VDF data.
+pub struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+    /// Key names for files that store keys as indices into a string table (appinfo.vdf v29).
+    keys: Option<&'a [String]>,
+    depth: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0, keys: None, depth: 0 }
+    }
+
+    pub fn with_keys(mut self, keys: &'a [String]) -> Self {
+        self.keys = Some(keys);
+        self
+    }
+
+    pub fn error(&self, message: impl Into<String>) -> Error {
+        Error { offset: self.offset, message: message.into() }
+    }
+
+    pub fn bytes(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        let end = self.offset.checked_add(len).filter(|end| *end <= self.data.len())
+            .ok_or_else(|| self.error("unexpected end of file"))?;
+        let bytes = &self.data[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn u32(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().expect("4 bytes")))
+    }
+
+    pub fn u64(&mut self) -> Result<u64, Error> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().expect("8 bytes")))
+    }
+
+    pub fn cstring(&mut self) -> Result<String, Error> {
+        let len = self.data[self.offset..].iter().position(|byte| *byte == 0)
+            .ok_or_else(|| self.error("unterminated string"))?;
+        let string = String::from_utf8_lossy(self.bytes(len)?).into_owned();
+        self.offset += 1;
+        Ok(string)
+    }
+
+    fn wide_string(&mut self) -> Result<String, Error> {
+        let mut units = vec![];
+        loop {
+            let unit = u16::from_le_bytes(self.bytes(2)?.try_into().expect("2 bytes"));
+            if unit == 0 {
+                return Ok(String::from_utf16_lossy(&units));
+            }
+            units.push(unit);
+        }
+    }
+
+    fn key(&mut self) -> Result<String, Error> {
+        if self.keys.is_none() {
+            return self.cstring();
+        }
+        let index = self.u32()? as usize;
+        let key = self.keys.and_then(|keys| keys.get(index)).cloned();
+        key.ok_or_else(|| self.error(format!("key index {index} outside the string table")))
+    }
+
+    /// Reads entries up to and including the end marker of the current table.
+    pub fn table(&mut self) -> Result<Value, Error> {
+        let mut entries = vec![];
+        loop {
+            let start = self.offset;
+            let kind = self.u8()?;
+            if kind == END || kind == ALTERNATE_END {
+                return Ok(Value::Table(entries));
+            }
+            let key = self.key()?;
+            let value = match kind {
+                TABLE if self.depth >= MAX_DEPTH => return Err(Error { offset: start, message: "nesting too deep".to_owned() }),
+                TABLE => {
+                    self.depth += 1;
+                    let table = self.table();
+                    self.depth -= 1;
+                    table?
+                }
+                STRING => Value::String(self.cstring()?),
+                WIDE_STRING => Value::String(self.wide_string()?),
+                INT32 | POINTER | COLOR => Value::Int(self.u32()? as i32 as i64),
+                FLOAT32 => Value::Float(f32::from_le_bytes(self.bytes(4)?.try_into().expect("4 bytes"))),
+                UINT64 | INT64 => Value::Int(self.u64()? as i64),
+                kind => return Err(Error { offset: start, message: format!("unknown value type {kind:#04x}") }),
+            };
+            entries.push((key, value));
+        }
+    }
+}
+
+/// Writes binary VDF, for building test fixtures.
+#[cfg(test)]
+pub fn write(value: &Value, keys: Option<&mut Vec<String>>) -> Vec<u8> {
+    fn write_table(entries: &[(String, Value)], out: &mut Vec<u8>, mut keys: Option<&mut Vec<String>>) {
+        for (key, value) in entries {
+            out.push(match value {
+                Value::Table(_) => TABLE,
+                Value::String(_) => STRING,
+                Value::Int(_) => INT32,
+                Value::Float(_) => FLOAT32,
+            });
+            match keys.as_deref_mut() {
+                Some(keys) => {
+                    let index = keys.iter().position(|existing| existing == key).unwrap_or_else(|| {
+                        keys.push(key.clone());
+                        keys.len() - 1
+                    });
+                    out.extend((index as u32).to_le_bytes());
+                }
+                None => {
+                    out.extend(key.as_bytes());
+                    out.push(0);
+                }
+            }
+            match value {
+                Value::Table(entries) => write_table(entries, out, keys.as_deref_mut()),
+                Value::String(value) => {
+                    out.extend(value.as_bytes());
+                    out.push(0);
+                }
+                Value::Int(value) => out.extend((*value as i32).to_le_bytes()),
+                Value::Float(value) => out.extend(value.to_le_bytes()),
+            }
+        }
+        out.push(END);
+    }
+
+    let mut out = vec![];
+    write_table(value.entries(), &mut out, keys);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read() {
+        let value = Value::Table(vec![
+            ("shortcuts".to_owned(), Value::Table(vec![
+                ("0".to_owned(), Value::Table(vec![
+                    ("AppName".to_owned(), Value::String("Celeste".to_owned())),
+                    ("appid".to_owned(), Value::Int(-123)),
+                    ("tags".to_owned(), Value::Table(vec![])),
+                ])),
+            ])),
+        ]);
+        let data = write(&value, None);
+        assert_eq!(Reader::new(&data).table().unwrap(), value);
+        assert_eq!(value.lookup(&["Shortcuts", "0", "APPNAME"]).and_then(Value::as_str), Some("Celeste"));
+
+        let error = Reader::new(&data[..data.len() - 1]).table().unwrap_err();
+        assert_eq!(error.message, "unexpected end of file");
+        let error = Reader::new(&[0x09, b'a', 0]).table().unwrap_err();
+        assert_eq!(error.to_string(), "unknown value type 0x09 at byte 0");
+
+        let mut keys = vec![];
+        let data = write(&value, Some(&mut keys));
+        assert_eq!(Reader::new(&data).with_keys(&keys).table().unwrap(), value);
+        let error = Reader::new(&data).with_keys(&[]).table().unwrap_err();
+        assert_eq!(error.message, "key index 0 outside the string table");
+
+        // Tables with empty keys, nested until the stack would overflow.
+        let data = vec![0; 1 << 20];
+        let error = Reader::new(&data).table().unwrap_err();
+        assert_eq!(error.to_string(), "nesting too deep at byte 128");
+        let mut nested = Value::Table(vec![]);
+        for _ in 0..MAX_DEPTH {
+            nested = Value::Table(vec![("".to_owned(), nested)]);
+        }
+        assert_eq!(Reader::new(&write(&nested, None)).table().unwrap(), nested);
+    }
+}
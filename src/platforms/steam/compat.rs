@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+use std::fs::read_dir;
+use std::path::{Path, PathBuf};
+
+use crate::{DetectContext, DetectError, ProcessInfo};
+use super::library::{installed_app, library_folders, steam_roots};
+use super::vdf;
+
+/// How an app is run through a compatibility tool such as Proton.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompatibilityInfo {
+    /// The tool's internal name, such as `proton_experimental`, from the user's choice in
+    /// `config/config.vdf` or else the name of the tool's folder.
+    pub tool: Option<String>,
+    /// Where the tool is installed, from `STEAM_COMPAT_TOOL_PATHS`.
+    pub tool_path: Option<PathBuf>,
+    /// The version in the tool's `version` file, such as `proton-9.0-4`.
+    pub tool_version: Option<String>,
+    /// The app's `compatdata/<app id>` folder, which holds its Wine prefix.
+    pub prefix: Option<PathBuf>,
+    /// The version of the tool that last updated the prefix, from its `version` file.
+    pub prefix_version: Option<String>,
+    /// The `STEAM_COMPAT_*` variables the app was launched with.
+    pub environment: BTreeMap<String, String>,
+}
+
+/// The compatibility tool `app_id` runs with, taken from the environment of its `process` when
+/// there is one and from Steam's config otherwise. `None` if neither shows a tool in use. Steam's
+/// default tool isn't considered, as it only applies to apps without a native build.
+pub fn compatibility_info(context: &DetectContext, app_id: i64, process: Option<&ProcessInfo>) -> Result<Option<CompatibilityInfo>, DetectError> {
+    let environment: BTreeMap<String, String> = process
+        .map(|process| process.environ.iter()
+            .filter(|(key, _)| key.starts_with("STEAM_COMPAT_"))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect())
+        .unwrap_or_default();
+    let tool_path = tool_path(&environment);
+    let mapped_tool = tool_mapping(context, app_id)?;
+    if tool_path.is_none() && mapped_tool.is_none() && !environment.contains_key("STEAM_COMPAT_DATA_PATH") {
+        return Ok(None);
+    }
+
+    let prefix = match environment.get("STEAM_COMPAT_DATA_PATH") {
+        Some(prefix) => Some(PathBuf::from(prefix)),
+        None => compatdata_dir(context, app_id)?,
+    };
+    Ok(Some(CompatibilityInfo {
+        tool: mapped_tool.or_else(|| Some(tool_path.as_ref()?.file_name()?.to_string_lossy().into_owned())),
+        tool_version: tool_path.as_ref().and_then(|path| read_version(context, path)),
+        prefix_version: prefix.as_ref().and_then(|path| read_version(context, path)),
+        tool_path,
+        prefix,
+        environment,
+    }))
+}
+
+// The first of the tool paths is the tool itself; the rest are runtimes it runs in.
+fn tool_path(environment: &BTreeMap<String, String>) -> Option<PathBuf> {
+    environment.get("STEAM_COMPAT_TOOL_PATHS")
+        .and_then(|paths| paths.split(':').find(|path| !path.is_empty()))
+        .map(PathBuf::from)
+}
+
+/// The `version` files [`compatibility_info`] can read: those of every prefix and of the tools
+/// running processes use.
+pub(crate) fn version_files(context: &DetectContext) -> Result<Vec<PathBuf>, DetectError> {
+    let mut files: Vec<PathBuf> = context.processes().infos().iter()
+        .filter_map(|process| tool_path(&process.environ))
+        .map(|path| path.join("version"))
+        .collect();
+    for library in library_folders(context)? {
+        let compatdata = library.join("steamapps").join("compatdata");
+        if let Ok(entries) = read_dir(context.path(&compatdata)) {
+            files.extend(entries.flatten().map(|entry| compatdata.join(entry.file_name()).join("version")));
+        }
+    }
+    Ok(files)
+}
+
+// Per-app choices live under `CompatToolMapping/<app id>`; the entry for app 0 is the default.
+fn tool_mapping(context: &DetectContext, app_id: i64) -> Result<Option<String>, DetectError> {
+    for root in steam_roots(context)? {
+        let path = context.path(root.join("config").join("config.vdf"));
+        if !path.exists() {
+            continue;
+        }
+        let config = vdf::read(&path)?;
+        let name = vdf::lookup(&config, &["InstallConfigStore", "Software", "Valve", "Steam", "CompatToolMapping", &app_id.to_string()])
+            .and_then(|mapping| vdf::get_str(mapping, "name"))
+            .filter(|name| !name.is_empty());
+        if let Some(name) = name {
+            return Ok(Some(name.to_owned()));
+        }
+    }
+    Ok(None)
+}
+
+// The prefix is kept in the library the app is installed in.
+fn compatdata_dir(context: &DetectContext, app_id: i64) -> Result<Option<PathBuf>, DetectError> {
+    let mut libraries = library_folders(context)?;
+    if let Some(app) = installed_app(context, app_id)? {
+        libraries.sort_by_key(|library| *library != app.library);
+    }
+    Ok(libraries.into_iter()
+        .map(|library| library.join("steamapps").join("compatdata").join(app_id.to_string()))
+        .find(|dir| context.path(dir).is_dir()))
+}
+
+// Proton's own `version` file starts with a build timestamp, as in `1718000000 proton-9.0-2`.
+fn read_version(context: &DetectContext, dir: &Path) -> Option<String> {
+    let version = std::fs::read_to_string(context.path(dir.join("version"))).ok()?;
+    version.split_whitespace().last().map(str::to_owned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_compatibility_info() {
+        let home = std::env::temp_dir().join(format!("game-detect-steam-compat-{}", std::process::id()));
+        let root = home.join(".local/share/Steam");
+        let proton = root.join("steamapps/common/Proton - Experimental");
+        let prefix = root.join("steamapps/compatdata/620");
+        std::fs::create_dir_all(&proton).unwrap();
+        std::fs::create_dir_all(&prefix).unwrap();
+        std::fs::create_dir_all(root.join("config")).unwrap();
+        std::fs::write(proton.join("version"), "1718000000 experimental-9.0-20240610\n").unwrap();
+        std::fs::write(prefix.join("version"), "9.0-200\n").unwrap();
+        std::fs::write(root.join("steamapps/appmanifest_620.acf"), r#""AppState" { "appid" "620" "name" "Portal 2" "installdir" "Portal 2" }"#).unwrap();
+        std::fs::write(root.join("config/config.vdf"), r#""InstallConfigStore" { "Software" { "Valve" { "Steam" { "CompatToolMapping"
+{
+	"0" { "name" "proton_9" "config" "" "priority" "75" }
+	"620" { "name" "proton_experimental" "config" "" "priority" "250" }
+} } } } }"#).unwrap();
+        let context = DetectContext::new(vec![], Some(home.clone()));
+
+        let info = compatibility_info(&context, 620, None).unwrap().unwrap();
+        assert_eq!(info.tool.as_deref(), Some("proton_experimental"));
+        assert_eq!(info.tool_path, None);
+        assert_eq!(info.prefix, Some(prefix.clone()));
+        assert_eq!(info.prefix_version.as_deref(), Some("9.0-200"));
+        assert!(compatibility_info(&context, 400, None).unwrap().is_none());
+
+        let process = ProcessInfo {
+            pid: 1,
+            environ: [
+                ("STEAM_COMPAT_TOOL_PATHS", format!("{}:/runtime", proton.display())),
+                ("STEAM_COMPAT_DATA_PATH", prefix.display().to_string()),
+                ("SteamAppId", "400".to_owned()),
+            ].into_iter().map(|(key, value)| (key.to_owned(), value)).collect(),
+            ..Default::default()
+        };
+        let info = compatibility_info(&context, 400, Some(&process)).unwrap().unwrap();
+        assert_eq!(info.tool.as_deref(), Some("Proton - Experimental"));
+        assert_eq!(info.tool_path, Some(proton.clone()));
+        assert_eq!(info.tool_version.as_deref(), Some("experimental-9.0-20240610"));
+        assert_eq!(info.prefix, Some(prefix));
+        assert_eq!(info.environment.len(), 2);
+        std::fs::remove_dir_all(&home).unwrap();
+    }
+}
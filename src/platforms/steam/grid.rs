@@ -0,0 +1,117 @@
+use std::path::{Path, PathBuf};
+
+use crate::{DetectContext, DetectError};
+use super::library::userdata_dirs;
+use super::users::active_user;
+
+const EXTENSIONS: [&str; 3] = ["png", "jpg", "jpeg"];
+
+/// Custom artwork a user set in their Steam library, from `userdata/<account id>/config/grid`.
+/// Paths are as the game sees them; pass them through [`DetectContext::path`] before reading them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GridArtwork {
+    /// The portrait capsule shown in the library grid.
+    pub capsule: Option<PathBuf>,
+    /// The landscape capsule shown in recent games.
+    pub wide_capsule: Option<PathBuf>,
+    pub hero: Option<PathBuf>,
+    pub logo: Option<PathBuf>,
+    pub icon: Option<PathBuf>,
+}
+
+impl GridArtwork {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The image best suited as a cover: the portrait capsule, then the hero, then the landscape
+    /// capsule.
+    pub fn cover(&self) -> Option<&Path> {
+        self.capsule.as_deref().or(self.hero.as_deref()).or(self.wide_capsule.as_deref())
+    }
+}
+
+/// The custom artwork for `app_id`, from the active user or else the first user that has any.
+pub fn grid_artwork(context: &DetectContext, app_id: i64) -> Result<GridArtwork, DetectError> {
+    let mut dirs = userdata_dirs(context)?;
+    if let Some(active_user) = active_user(context)? {
+        dirs.sort_by_key(|(user_id, _)| *user_id != active_user);
+    }
+    Ok(dirs.into_iter()
+        .map(|(_, dir)| user_grid_artwork(context, &dir, app_id, None))
+        .find(|artwork| !artwork.is_empty())
+        .unwrap_or_default())
+}
+
+/// The artwork every user set for `apps`, given as app ids and the legacy ids of shortcuts.
+pub(crate) fn grid_files(context: &DetectContext, apps: &[(i64, Option<u64>)]) -> Result<Vec<PathBuf>, DetectError> {
+    let mut files = vec![];
+    for (_, userdata) in userdata_dirs(context)? {
+        for (app_id, legacy_id) in apps {
+            let artwork = user_grid_artwork(context, &userdata, *app_id, *legacy_id);
+            files.extend([artwork.capsule, artwork.wide_capsule, artwork.hero, artwork.logo, artwork.icon].into_iter().flatten());
+        }
+    }
+    Ok(files)
+}
+
+/// The artwork in one user's grid folder. Landscape capsules of shortcuts set by older clients
+/// are named after the 64-bit `legacy_id` instead.
+pub(crate) fn user_grid_artwork(context: &DetectContext, userdata: &Path, app_id: i64, legacy_id: Option<u64>) -> GridArtwork {
+    let grid = userdata.join("config").join("grid");
+    let find = |suffix: &str| {
+        EXTENSIONS.iter()
+            .map(|extension| grid.join(format!("{app_id}{suffix}.{extension}")))
+            .find(|path| context.path(path).is_file())
+    };
+    GridArtwork {
+        capsule: find("p"),
+        wide_capsule: find("").or_else(|| {
+            let legacy_id = legacy_id?;
+            EXTENSIONS.iter()
+                .map(|extension| grid.join(format!("{legacy_id}.{extension}")))
+                .find(|path| context.path(path).is_file())
+        }),
+        hero: find("_hero"),
+        logo: find("_logo"),
+        icon: find("_icon"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_grid_artwork() {
+        let home = std::env::temp_dir().join(format!("game-detect-steam-grid-{}", std::process::id()));
+        let userdata = home.join(".local/share/Steam/userdata");
+        let first = userdata.join("1/config/grid");
+        let second = userdata.join("2/config/grid");
+        std::fs::create_dir_all(&first).unwrap();
+        std::fs::create_dir_all(&second).unwrap();
+        for file in ["620p.png", "620_hero.jpg", "620_icon.png", "620_logo.txt"] {
+            std::fs::write(second.join(file), "").unwrap();
+        }
+        std::fs::write(first.join("400_hero.jpeg"), "").unwrap();
+        std::fs::write(first.join("17293822569102704640.png"), "").unwrap();
+
+        let context = DetectContext::new(vec![], Some(home.clone()));
+        let artwork = grid_artwork(&context, 620).unwrap();
+        assert_eq!(artwork, GridArtwork {
+            capsule: Some(second.join("620p.png")),
+            hero: Some(second.join("620_hero.jpg")),
+            icon: Some(second.join("620_icon.png")),
+            ..Default::default()
+        });
+        assert_eq!(artwork.cover(), Some(second.join("620p.png").as_path()));
+        assert_eq!(grid_artwork(&context, 400).unwrap().cover(), Some(first.join("400_hero.jpeg").as_path()));
+        assert!(grid_artwork(&context, 440).unwrap().is_empty());
+
+        let legacy = user_grid_artwork(&context, &userdata.join("1"), 4026531840, Some(17293822569102704640));
+        assert_eq!(legacy.wide_capsule, Some(first.join("17293822569102704640.png")));
+        std::fs::remove_dir_all(&home).unwrap();
+    }
+}
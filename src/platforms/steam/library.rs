@@ -0,0 +1,331 @@
+use std::fs::{canonicalize, read_dir};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::{DetectContext, DetectError};
+use super::vdf;
+
+/// A game or tool installed in one of the Steam library folders, read from its
+/// `appmanifest_<id>.acf`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InstalledApp {
+    pub app_id: i64,
+    pub name: String,
+    /// The library folder the app is installed in.
+    pub library: PathBuf,
+    /// The full path of the app's `steamapps/common/<installdir>` folder.
+    pub install_dir: PathBuf,
+    pub size_on_disk: u64,
+    pub build_id: u64,
+    pub last_updated: Option<SystemTime>,
+    /// The raw `StateFlags` bit field.
+    pub state_flags: u32,
+    /// How much of a pending download or update has been fetched, in bytes.
+    pub bytes_downloaded: u64,
+    pub bytes_to_download: u64,
+}
+
+// Native, Flatpak and Snap installs each keep a `.steam` directory and a `.local/share/Steam`
+// below one of these, relative to the home directory.
+#[cfg(not(any(target_family = "windows", target_os = "macos")))]
+const STEAM_HOMES: [&str; 3] = ["", ".var/app/com.valvesoftware.Steam", "snap/steam/common"];
+
+/// The directories that hold a `.steam` directory, one for each way Steam can be installed.
+#[cfg(not(any(target_family = "windows", target_os = "macos")))]
+pub(crate) fn steam_homes(context: &DetectContext) -> Result<Vec<PathBuf>, DetectError> {
+    let home = context.home()?;
+    Ok(STEAM_HOMES.iter().map(|dir| if dir.is_empty() { home.to_path_buf() } else { home.join(dir) }).collect())
+}
+
+fn candidates(context: &DetectContext) -> Result<Vec<PathBuf>, DetectError> {
+    #[cfg(target_family = "windows")]
+    {
+        use registry::{Data, Hive, Security};
+        let from_registry = Hive::CurrentUser.open(super::REG_TREE_PATH, Security::Read).ok()
+            .and_then(|key| key.value("SteamPath").ok())
+            .and_then(|value| match value {
+                Data::String(path) => Some(PathBuf::from(path.to_string_lossy())),
+                _ => None,
+            });
+        Ok(from_registry.into_iter().chain([PathBuf::from(r"C:\Program Files (x86)\Steam")]).collect())
+    }
+    #[cfg(target_os = "macos")]
+    return Ok(vec![context.app_data_dir()?.join("Steam")]);
+    #[cfg(not(any(target_family = "windows", target_os = "macos")))]
+    {
+        let mut candidates = vec![];
+        for steam_home in steam_homes(context)? {
+            // `.steam/root` and `.steam/steam` link to the install.
+            let dot_steam = steam_home.join(".steam");
+            for link in ["root", "steam"] {
+                let link = dot_steam.join(link);
+                candidates.push(match std::fs::read_link(context.path(&link)) {
+                    Ok(target) => resolve(&dot_steam, &target),
+                    Err(_) => link,
+                });
+            }
+            candidates.push(steam_home.join(".local").join("share").join("Steam"));
+        }
+        Ok(candidates)
+    }
+}
+
+// Joins a symlink's target to the directory it is in, dropping `..` without touching the
+// filesystem, which might be a snapshot without the link's target.
+#[cfg(not(any(target_family = "windows", target_os = "macos")))]
+fn resolve(dir: &Path, target: &Path) -> PathBuf {
+    use std::path::Component;
+    let mut path = dir.to_path_buf();
+    for component in target.components() {
+        match component {
+            Component::ParentDir => {
+                path.pop();
+            }
+            Component::CurDir => {}
+            component => path.push(component),
+        }
+    }
+    path
+}
+
+/// Every Steam install, starting with the roots the context was given. An install is found through
+/// any of the paths that lead to it, but only reported once. Only `userdata` might exist when
+/// replaying a snapshot.
+pub fn steam_roots(context: &DetectContext) -> Result<Vec<PathBuf>, DetectError> {
+    let mut roots = vec![];
+    let mut seen = vec![];
+    for candidate in context.steam_roots().iter().cloned().chain(candidates(context)?) {
+        let path = context.path(&candidate);
+        if !path.join("steamapps").is_dir() && !path.join("userdata").is_dir() {
+            continue;
+        }
+        let real_path = canonicalize(&path).unwrap_or(path);
+        if !seen.contains(&real_path) {
+            seen.push(real_path);
+            roots.push(candidate);
+        }
+    }
+    Ok(roots)
+}
+
+/// The preferred Steam install, or `None` if Steam can't be found.
+pub fn steam_root(context: &DetectContext) -> Result<Option<PathBuf>, DetectError> {
+    Ok(steam_roots(context)?.into_iter().next())
+}
+
+/// Each Steam user's `userdata/<account id>` folder, ordered by install and then account id.
+pub(crate) fn userdata_dirs(context: &DetectContext) -> Result<Vec<(i64, PathBuf)>, DetectError> {
+    let mut dirs = vec![];
+    for root in steam_roots(context)? {
+        let userdata = root.join("userdata");
+        let Ok(entries) = read_dir(context.path(&userdata)) else {
+            continue;
+        };
+        let mut root_dirs: Vec<(i64, PathBuf)> = entries.flatten()
+            .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+            .map(|user_id: i64| (user_id, userdata.join(user_id.to_string())))
+            .collect();
+        root_dirs.sort();
+        dirs.extend(root_dirs);
+    }
+    Ok(dirs)
+}
+
+/// Every library folder of every install, each install starting with its root. Folders that don't
+/// exist, such as those on an unmounted drive, are left out.
+pub fn library_folders(context: &DetectContext) -> Result<Vec<PathBuf>, DetectError> {
+    let mut folders = vec![];
+    for root in steam_roots(context)? {
+        if !folders.contains(&root) && context.path(root.join("steamapps")).is_dir() {
+            folders.push(root.clone());
+        }
+        add_library_folders(context, &root, &mut folders)?;
+    }
+    Ok(folders)
+}
+
+fn add_library_folders(context: &DetectContext, root: &Path, folders: &mut Vec<PathBuf>) -> Result<(), DetectError> {
+    let libraryfolders_path = context.path(root.join("steamapps").join("libraryfolders.vdf"));
+    if libraryfolders_path.exists() {
+        let libraryfolders = vdf::read(&libraryfolders_path)?;
+        // Folders are keyed by their index. Older clients also have keys like "TimeNextStatsReport".
+        let mut entries: Vec<(u32, &vdf_reader::entry::Entry)> = vdf::get(&libraryfolders, "libraryfolders")
+            .and_then(|folders| folders.as_table())
+            .map(|folders| folders.iter().filter_map(|(key, entry)| Some((key.parse().ok()?, entry))).collect())
+            .unwrap_or_default();
+        entries.sort_by_key(|(index, _)| *index);
+        for (_, entry) in entries {
+            let path = match entry.as_str() {
+                Some(path) => Some(path),
+                None => vdf::get_str(entry, "path"),
+            };
+            if let Some(path) = path.map(PathBuf::from) {
+                if !folders.contains(&path) && context.path(&path).is_dir() {
+                    folders.push(path);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Every app installed in any library folder, ordered by library and then app id. Manifests that
+/// can't be parsed, for example because Steam is writing them, are skipped.
+pub fn installed_apps(context: &DetectContext) -> Result<Vec<InstalledApp>, DetectError> {
+    let mut apps = vec![];
+    for library in library_folders(context)? {
+        let steamapps = context.path(library.join("steamapps"));
+        let entries = read_dir(&steamapps).map_err(|e| DetectError::io(&steamapps, e))?;
+        let mut library_apps: Vec<InstalledApp> = entries.flatten()
+            .filter(|entry| is_manifest(&entry.file_name().to_string_lossy()))
+            .filter_map(|entry| read_manifest(&entry.path(), &library))
+            .collect();
+        library_apps.sort_by_key(|app| app.app_id);
+        apps.extend(library_apps);
+    }
+    Ok(apps)
+}
+
+fn is_manifest(name: &str) -> bool {
+    name.starts_with("appmanifest_") && name.ends_with(".acf")
+}
+
+/// Every install's `libraryfolders.vdf` and the manifests in each library folder.
+pub(crate) fn library_files(context: &DetectContext) -> Result<Vec<PathBuf>, DetectError> {
+    let mut files: Vec<PathBuf> = steam_roots(context)?.into_iter()
+        .map(|root| root.join("steamapps").join("libraryfolders.vdf"))
+        .collect();
+    for library in library_folders(context)? {
+        let steamapps = library.join("steamapps");
+        let Ok(entries) = read_dir(context.path(&steamapps)) else {
+            continue;
+        };
+        files.extend(entries.flatten()
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .filter(|name| is_manifest(name))
+            .map(|name| steamapps.join(name)));
+    }
+    Ok(files)
+}
+
+/// The manifest of `app_id` from whichever library folder it is installed in.
+pub fn installed_app(context: &DetectContext, app_id: i64) -> Result<Option<InstalledApp>, DetectError> {
+    for library in library_folders(context)? {
+        let path = context.path(library.join("steamapps").join(format!("appmanifest_{app_id}.acf")));
+        if let Some(app) = read_manifest(&path, &library) {
+            return Ok(Some(app));
+        }
+    }
+    Ok(None)
+}
+
+fn read_manifest(path: &Path, library: &Path) -> Option<InstalledApp> {
+    let manifest = vdf::read(path).ok()?;
+    let state = vdf::get(&manifest, "AppState")?;
+    let install_dir = vdf::get_str(state, "installdir")?;
+    Some(InstalledApp {
+        app_id: vdf::get_parsed(state, "appid")?,
+        name: vdf::get_str(state, "name").unwrap_or_default().to_owned(),
+        library: library.to_path_buf(),
+        install_dir: library.join("steamapps").join("common").join(install_dir),
+        size_on_disk: vdf::get_parsed(state, "SizeOnDisk").unwrap_or_default(),
+        build_id: vdf::get_parsed(state, "buildid").unwrap_or_default(),
+        last_updated: vdf::get_parsed::<u64>(state, "LastUpdated")
+            .filter(|seconds| *seconds != 0)
+            .map(|seconds| SystemTime::UNIX_EPOCH + Duration::from_secs(seconds)),
+        state_flags: vdf::get_parsed(state, "StateFlags").unwrap_or_default(),
+        bytes_downloaded: vdf::get_parsed(state, "BytesDownloaded").unwrap_or_default(),
+        bytes_to_download: vdf::get_parsed(state, "BytesToDownload").unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, write};
+
+    fn manifest(app_id: i64, name: &str, install_dir: &str) -> String {
+        format!(r#""AppState"
+{{
+	"appid"		"{app_id}"
+	"name"		"{name}"
+	"StateFlags"		"4"
+	"installdir"		"{install_dir}"
+	"LastUpdated"		"1700000000"
+	"SizeOnDisk"		"12345"
+	"buildid"		"999"
+	"InstalledDepots" {{ "621" {{ "manifest" "1" "size" "2" }} }}
+}}"#)
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_installed_apps() {
+        let home = std::env::temp_dir().join(format!("game-detect-steam-library-{}", std::process::id()));
+        let root = home.join(".local/share/Steam");
+        let library = home.join("games/SteamLibrary");
+        create_dir_all(root.join("steamapps")).unwrap();
+        create_dir_all(library.join("steamapps")).unwrap();
+        write(root.join("steamapps/libraryfolders.vdf"), format!(r#""libraryfolders"
+{{
+	"0" {{ "path" "{}" "apps" {{ "620" "12345" }} }}
+	"1" {{ "path" "{}" }}
+	"2" {{ "path" "/mnt/unplugged" }}
+}}"#, root.display(), library.display())).unwrap();
+        write(root.join("steamapps/appmanifest_620.acf"), manifest(620, "Portal 2", "Portal 2")).unwrap();
+        write(root.join("steamapps/appmanifest_400.acf"), manifest(400, "Portal", "Portal")).unwrap();
+        write(root.join("steamapps/appmanifest_1.acf"), "\"AppState\" {").unwrap();
+        write(library.join("steamapps/appmanifest_440.acf"), manifest(440, "Team Fortress 2", "Team Fortress 2")).unwrap();
+        write(library.join("steamapps/libraryfolder.vdf"), "").unwrap();
+
+        let context = DetectContext::new(vec![], Some(home.clone()));
+        assert_eq!(steam_root(&context).unwrap(), Some(root.clone()));
+        assert_eq!(library_folders(&context).unwrap(), vec![root.clone(), library.clone()]);
+
+        let apps = installed_apps(&context).unwrap();
+        assert_eq!(apps.iter().map(|app| app.app_id).collect::<Vec<_>>(), vec![400, 620, 440]);
+        assert_eq!(apps[1], InstalledApp {
+            app_id: 620,
+            name: "Portal 2".to_owned(),
+            library: root.clone(),
+            install_dir: root.join("steamapps/common/Portal 2"),
+            size_on_disk: 12345,
+            build_id: 999,
+            last_updated: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1700000000)),
+            state_flags: 4,
+            bytes_downloaded: 0,
+            bytes_to_download: 0,
+        });
+        assert_eq!(apps[2].library, library);
+        assert_eq!(installed_app(&context, 440).unwrap(), Some(apps[2].clone()));
+        assert_eq!(installed_app(&context, 1).unwrap(), None);
+        std::fs::remove_dir_all(&home).unwrap();
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_steam_roots() {
+        let home = std::env::temp_dir().join(format!("game-detect-steam-roots-{}", std::process::id()));
+        let native = home.join(".local/share/Steam");
+        let flatpak = home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam");
+        let portable = home.join("portable/Steam");
+        for root in [&native, &flatpak, &portable] {
+            create_dir_all(root.join("steamapps")).unwrap();
+        }
+        create_dir_all(home.join(".steam")).unwrap();
+        std::os::unix::fs::symlink("../.local/share/Steam", home.join(".steam/root")).unwrap();
+        std::os::unix::fs::symlink(&native, home.join(".steam/steam")).unwrap();
+        create_dir_all(home.join(".var/app/com.valvesoftware.Steam/.steam")).unwrap();
+        write(home.join(".var/app/com.valvesoftware.Steam/.steam/registry.vdf"), r#""Registry" { "HKCU" { "Software" { "Valve" { "Steam" { "RunningAppID" "620" } } } } }"#).unwrap();
+
+        let context = DetectContext::new(vec![], Some(home.clone()));
+        assert_eq!(steam_roots(&context).unwrap(), vec![native.clone(), flatpak.clone()]);
+        assert_eq!(super::super::registry_running_app_id(&context).unwrap(), Some(620));
+
+        let context = context.with_steam_roots(vec![portable.clone(), home.join("missing")]);
+        assert_eq!(steam_roots(&context).unwrap(), vec![portable.clone(), native.clone(), flatpak.clone()]);
+        assert_eq!(library_folders(&context).unwrap().len(), 3);
+        std::fs::remove_dir_all(&home).unwrap();
+    }
+}
@@ -0,0 +1,478 @@
+#[cfg(feature = "http")]
+use crate::{FetchError, GameInfo, InfoOptions};
+use crate::{DetectContext, DetectError, DetectedGame, Detection, Detector, ProcessEntry, ProcessInfo};
+use std::path::PathBuf;
+#[cfg(target_family = "windows")]
+use registry::{Hive, Security, Data};
+
+#[cfg(target_family = "windows")]
+const REG_TREE_PATH: &str = r"Software\Valve\Steam";
+
+mod appinfo;
+mod binary_vdf;
+mod compat;
+mod grid;
+mod library;
+mod playtime;
+mod shortcuts;
+mod state;
+#[cfg(feature = "http")]
+mod store;
+mod users;
+mod vdf;
+
+pub use appinfo::{all_app_info, app_info, app_type, AppAssets, AppInfo, AppType};
+pub use compat::{compatibility_info, CompatibilityInfo};
+pub use grid::{grid_artwork, GridArtwork};
+pub use library::{installed_app, installed_apps, library_folders, steam_root, steam_roots, InstalledApp};
+pub use playtime::{playtime, playtimes, AppPlaytime};
+pub use shortcuts::{shortcut_app_id, shortcuts, Shortcut};
+pub use state::{app_state, AppState};
+#[cfg(feature = "http")]
+pub use store::{store_details, Metacritic, Screenshot, StoreDetails, StorePlatforms, StoreTag};
+#[cfg(feature = "steam-account")]
+pub use users::{active_account, active_user, SteamAccount};
+
+#[derive(Debug, Clone, Default)]
+pub struct SteamDetector {
+    exclude_non_games: bool,
+}
+
+impl SteamDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Leaves out apps Steam doesn't classify as games, such as Proton, the Steam Linux Runtime
+    /// and SteamVR. Non-Steam shortcuts are always kept.
+    pub fn exclude_non_games(mut self, exclude_non_games: bool) -> Self {
+        self.exclude_non_games = exclude_non_games;
+        self
+    }
+}
+
+impl Detector for SteamDetector {
+    fn name(&self) -> &str {
+        "steam"
+    }
+
+    fn detect(&self, context: &DetectContext) -> Result<Vec<Detection>, DetectError> {
+        let detections = detect_games(context)?;
+        if !self.exclude_non_games {
+            return Ok(detections);
+        }
+        let app_ids: Vec<i64> = detections.iter()
+            .filter_map(|detection| match detection.game {
+                DetectedGame::Steam { id, .. } => Some(id),
+                _ => None,
+            })
+            .collect();
+        let app_types = appinfo::app_types(context, &app_ids)?;
+        Ok(detections.into_iter()
+            .filter(|detection| match detection.game {
+                DetectedGame::Steam { id, .. } => app_types.iter().all(|(app_id, app_type)| *app_id != id || app_type.is_game()),
+                _ => true,
+            })
+            .collect())
+    }
+
+    fn reads_environ(&self, key: &str) -> bool {
+        APP_ID_VARIABLES.contains(&key) || key.starts_with("STEAM_COMPAT_")
+    }
+
+    fn data_files(&self, context: &DetectContext) -> Vec<PathBuf> {
+        let mut files = vec![];
+        #[cfg(any(target_os = "linux", target_os = "macos"))]
+        files.extend(registry_paths(context).unwrap_or_default());
+        for root in steam_roots(context).unwrap_or_default() {
+            files.push(root.join("config").join("config.vdf"));
+            files.push(root.join("config").join("loginusers.vdf"));
+            // Often over 100 MB, and only read to tell games from tools.
+            if self.exclude_non_games {
+                files.push(root.join("appcache").join("appinfo.vdf"));
+            }
+        }
+        files.extend(library::library_files(context).unwrap_or_default());
+        files.extend(compat::version_files(context).unwrap_or_default());
+        // Only the artwork of the games running is read, and a grid folder can hold thousands.
+        let running: Vec<(i64, Option<u64>)> = detect_games(context).unwrap_or_default().into_iter()
+            .filter_map(|detection| match detection.game {
+                DetectedGame::Steam { id, .. } => Some((id, None)),
+                DetectedGame::SteamShortcut { id, shortcut } => Some((id, Some(shortcut.game_id()))),
+                _ => None,
+            })
+            .collect();
+        files.extend(grid::grid_files(context, &running).unwrap_or_default());
+        files.extend(shortcuts::shortcut_files(context).unwrap_or_default().into_iter().map(|(_, path)| path));
+        files
+    }
+}
+
+// On Linux, the native, Flatpak and Snap installs each have their own registry.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn registry_paths(context: &DetectContext) -> Result<Vec<PathBuf>, DetectError> {
+    #[cfg(target_os = "linux")]
+    return Ok(library::steam_homes(context)?.into_iter().map(|steam_home| steam_home.join(".steam").join("registry.vdf")).collect());
+    #[cfg(target_os = "macos")]
+    return Ok(vec![context.app_data_dir()?.join("Steam").join("registry.vdf")]);
+}
+
+/// A custom icon from the user's grid folder is used over the store's capsule. Compatibility
+/// tool details are best effort and don't fail the detection.
+fn detection(context: &DetectContext, appid: i64, process: Option<ProcessInfo>) -> Detection {
+    let custom_icon = grid_artwork(context, appid).ok().and_then(|artwork| artwork.icon);
+    let compatibility = compatibility_info(context, appid, process.as_ref()).ok().flatten();
+    Detection::new(DetectedGame::Steam {
+        id: appid,
+        url: format!("https://store.steampowered.com/app/{0}", appid),
+        icon: match custom_icon {
+            Some(icon) => icon.to_string_lossy().into_owned(),
+            None => format!("https://cdn.cloudflare.steamstatic.com/steam/apps/{0}/hero_capsule.jpg", appid),
+        },
+        compatibility: compatibility.map(Box::new),
+    }, process)
+}
+
+fn shortcut_detection(shortcut: &Shortcut, process: Option<ProcessInfo>) -> Detection {
+    Detection::new(DetectedGame::SteamShortcut { id: shortcut.app_id, shortcut: Box::new(shortcut.clone()) }, process)
+}
+
+// Steam sets these for everything it launches. For non-Steam shortcuts SteamGameId holds a 64-bit
+// game id with the shortcut's app id in its upper half.
+const APP_ID_VARIABLES: [&str; 3] = ["SteamAppId", "STEAM_COMPAT_APP_ID", "SteamGameId"];
+
+/// The app id a process was launched for, from its environment or, for Steam's reaper, from the
+/// `AppId=` argument. The reaper's arguments stay readable when the environment isn't.
+fn process_app_id(process: ProcessEntry) -> Option<i64> {
+    let from_environ = APP_ID_VARIABLES.iter()
+        .filter_map(|variable| process.info.environ.get(*variable))
+        .filter_map(|value| value.parse::<u64>().ok())
+        .map(|id| if id > u32::MAX as u64 { id >> 32 } else { id })
+        .find(|appid| *appid > 0)
+        .map(|appid| appid as i64);
+    from_environ.or_else(|| {
+        if !process.cmdline.contains("SteamLaunch") {
+            return None;
+        }
+        process.info.cmdline.iter()
+            .find_map(|arg| arg.strip_prefix("AppId="))
+            .and_then(|appid| appid.parse::<i64>().ok())
+            .filter(|appid| *appid > 0 && *appid <= u32::MAX as i64)
+    })
+}
+
+/// Every running app, reported once with the outermost process of its process tree. Children
+/// inherit the launch environment, so a process whose parent runs the same app is skipped.
+fn running_apps(context: &DetectContext) -> Vec<(i64, Option<ProcessInfo>)> {
+    let processes = context.processes();
+    let mut running: Vec<(i64, Option<ProcessInfo>)> = vec![];
+    for process in processes.iter() {
+        let Some(appid) = process_app_id(process) else {
+            continue;
+        };
+        let parent = process.info.ppid.and_then(|ppid| processes.get(ppid));
+        if parent.and_then(process_app_id) == Some(appid) {
+            continue;
+        }
+        if running.iter().any(|(id, _)| *id == appid) {
+            continue;
+        }
+        running.push((appid, Some(process.info.clone())));
+    }
+    running
+}
+
+fn client_running(context: &DetectContext) -> bool {
+    let processes = context.processes();
+    processes.by_exe_name("steam").next().is_some() || processes.by_exe_name("steam.exe").next().is_some()
+}
+
+/// Finds running Steam games from the processes Steam launched. `RunningAppID` is only used when
+/// no process can be attributed to a game, and only while the client is running, because Steam
+/// leaves it behind when it crashes. Without a process list it is trusted as is.
+///
+/// Non-Steam shortcuts are reported as [`DetectedGame::SteamShortcut`], and only if they can be
+/// found in a `shortcuts.vdf`.
+pub fn detect_games(context: &DetectContext) -> Result<Vec<Detection>, DetectError> {
+    let mut running = running_apps(context);
+    let running_app_id = registry_running_app_id(context)?;
+    match running_app_id {
+        Some(appid) if running.is_empty() && (context.processes().is_empty() || client_running(context)) => {
+            running.push((appid, None));
+        }
+        Some(_) if running.is_empty() => {}
+        // The app Steam considers running goes first.
+        Some(appid) => running.sort_by_key(|(id, _)| *id != appid),
+        None => {}
+    }
+
+    let known_shortcuts = if running.iter().any(|(appid, _)| shortcuts::is_shortcut_app_id(*appid)) {
+        shortcuts(context)?
+    } else {
+        vec![]
+    };
+    Ok(running.into_iter()
+        .filter_map(|(appid, process)| {
+            if !shortcuts::is_shortcut_app_id(appid) {
+                return Some(detection(context, appid, process));
+            }
+            let shortcut = known_shortcuts.iter().find(|shortcut| shortcut.app_id == appid)?;
+            Some(shortcut_detection(shortcut, process))
+        })
+        .collect())
+}
+
+// RunningAppID is 0 while no game is running.
+#[cfg_attr(target_family = "windows", allow(unused_variables))]
+fn registry_running_app_id(context: &DetectContext) -> Result<Option<i64>, DetectError> {
+    #[cfg(target_family = "windows")]
+    {
+        let regkey = match Hive::CurrentUser.open(REG_TREE_PATH, Security::Read) {
+            Ok(regkey) => regkey,
+            Err(_) => return Ok(None),
+        };
+        let val = match regkey.value("RunningAppID") {
+            Ok(val) => val,
+            Err(_) => return Ok(None),
+        };
+        match val {
+            Data::U32(appid) => Ok(Some(appid as i64).filter(|appid| *appid != 0)),
+            Data::U64(appid) => Ok(Some(appid as i64).filter(|appid| *appid != 0)),
+            _ => Ok(None)
+        }
+    }
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    {
+        for registry_path in registry_paths(context)? {
+            let registry_path = context.path(registry_path);
+            if !registry_path.exists() {
+                continue;
+            }
+            let registry = vdf::read(&registry_path)?;
+            let appid = vdf::lookup(&registry, &["Registry", "HKCU", "Software", "Valve", "Steam", "RunningAppID"])
+                .and_then(|appid| appid.as_str())
+                .and_then(|appid| appid.parse::<i64>().ok())
+                .filter(|appid| *appid != 0);
+            if appid.is_some() {
+                return Ok(appid);
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Fetches info from the store API, falling back to the client's metadata cache when the store
+/// can't be reached. A cover the user set in their library replaces the store's.
+#[cfg(feature = "http")]
+pub(crate) async fn fetch_info(detected: &DetectedGame, options: &InfoOptions) -> Result<GameInfo, FetchError> {
+    match detected {
+        DetectedGame::Steam { id, icon, .. } => {
+            let context = DetectContext::without_processes(&options.detect_options);
+            let info = match store_details(*id, options).await {
+                Err(error @ (FetchError::Http(_) | FetchError::Status(_))) => match app_info(&context, *id) {
+                    Ok(Some(app)) => app.to_game_info(),
+                    _ => return Err(error),
+                },
+                result => result?.info,
+            };
+            let mut info = GameInfo { icon: icon.clone(), ..info };
+            if let Some(cover) = grid_artwork(&context, *id).unwrap_or_default().cover() {
+                info.cover = cover.to_string_lossy().into_owned();
+            }
+            Ok(info)
+        }
+        DetectedGame::SteamShortcut { shortcut, .. } => Ok(shortcut.to_game_info()),
+        _ => Err(FetchError::Unsupported(detected.platform()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_os = "linux")]
+    fn process(pid: i32, ppid: i32, cmdline: &[&str], environ: &[(&str, &str)]) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            ppid: Some(ppid),
+            exe: cmdline.first().map(PathBuf::from),
+            cmdline: cmdline.iter().map(|arg| arg.to_string()).collect(),
+            environ: environ.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn detected(detections: Vec<Detection>) -> Vec<(i64, Option<i32>)> {
+        detections.into_iter()
+            .map(|detection| match detection.game {
+                DetectedGame::Steam { id, .. } | DetectedGame::SteamShortcut { id, .. } => (id, detection.pid()),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_detect_games() {
+        let home = std::env::temp_dir().join(format!("game-detect-steam-{}", std::process::id()));
+        std::fs::create_dir_all(home.join(".steam")).unwrap();
+        std::fs::write(home.join(".steam/registry.vdf"), r#""Registry" { "HKCU" { "Software" { "Valve" { "Steam" { "RunningAppID" "440" } } } } }"#).unwrap();
+
+        let steam = process(10, 1, &["/home/user/.local/share/Steam/ubuntu12_32/steam"], &[]);
+        let processes = vec![
+            steam.clone(),
+            process(20, 10, &["/home/user/.local/share/Steam/ubuntu12_32/reaper", "SteamLaunch", "AppId=620", "--", "proton"], &[("SteamAppId", "620")]),
+            process(21, 20, &["/games/portal2/portal2_linux"], &[("SteamAppId", "620"), ("SteamGameId", "620")]),
+            process(30, 10, &["/games/tf2/hl2_linux"], &[("SteamGameId", "440")]),
+            process(31, 30, &["/games/tf2/helper"], &[("SteamGameId", "440")]),
+            // Another user's reaper, whose environment can't be read.
+            process(40, 1, &["reaper", "SteamLaunch", "AppId=570", "--", "dota2"], &[]),
+            process(50, 10, &["/games/shortcut"], &[("SteamAppId", "0"), ("SteamGameId", "12345678901234567890")]),
+            process(60, 1, &["/usr/bin/vim", "AppId=1"], &[]),
+        ];
+        let context = DetectContext::new(processes, Some(home.clone()));
+        assert_eq!(detected(detect_games(&context).unwrap()), vec![(440, Some(30)), (620, Some(20)), (570, Some(40))]);
+
+        // The registry is only used when no process is running a game, and only while Steam runs.
+        let context = DetectContext::new(vec![steam], Some(home.clone()));
+        assert_eq!(detected(detect_games(&context).unwrap()), vec![(440, None)]);
+        let context = DetectContext::new(vec![process(60, 1, &["/usr/bin/vim"], &[])], Some(home.clone()));
+        assert!(detect_games(&context).unwrap().is_empty());
+        let context = DetectContext::new(vec![], Some(home.clone()));
+        assert_eq!(detected(detect_games(&context).unwrap()), vec![(440, None)]);
+
+        let grid = home.join(".local/share/Steam/userdata/42/config/grid");
+        std::fs::create_dir_all(&grid).unwrap();
+        std::fs::write(grid.join("440_icon.png"), "").unwrap();
+        let icon = match detect_games(&context).unwrap().remove(0).game {
+            DetectedGame::Steam { icon, .. } => icon,
+            _ => unreachable!(),
+        };
+        assert_eq!(icon, grid.join("440_icon.png").to_string_lossy());
+        std::fs::remove_dir_all(&home).unwrap();
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_exclude_non_games() {
+        let processes = vec![
+            process(10, 1, &["/games/steamvr/vrmonitor"], &[("SteamAppId", "250820")]),
+            process(20, 1, &["/games/portal2/portal2_linux"], &[("SteamAppId", "620")]),
+        ];
+        let context = DetectContext::new(processes, Some(std::env::temp_dir().join("game-detect-steam-no-home")));
+        assert_eq!(detected(SteamDetector::new().detect(&context).unwrap()), vec![(250820, Some(10)), (620, Some(20))]);
+        assert_eq!(detected(SteamDetector::new().exclude_non_games(true).detect(&context).unwrap()), vec![(620, Some(20))]);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_detect_shortcuts() {
+        use binary_vdf::Value;
+
+        let home = std::env::temp_dir().join(format!("game-detect-steam-shortcuts-{}", std::process::id()));
+        let config = home.join(".local/share/Steam/userdata/42/config");
+        std::fs::create_dir_all(&config).unwrap();
+        std::fs::write(config.join("shortcuts.vdf"), shortcuts::tests::shortcuts_file(vec![vec![
+            ("AppName", Value::String("Celeste".to_owned())),
+            ("Exe", Value::String("\"/opt/celeste/Celeste\"".to_owned())),
+        ]])).unwrap();
+        let app_id = shortcut_app_id("\"/opt/celeste/Celeste\"", "Celeste");
+        let game_id = ((app_id as u64) << 32 | 0x02000000).to_string();
+
+        let processes = vec![
+            process(10, 1, &["/opt/celeste/Celeste"], &[("SteamAppId", "0"), ("SteamGameId", &game_id)]),
+            process(20, 1, &["/opt/unknown"], &[("SteamGameId", "12345678901234567890")]),
+        ];
+        let context = DetectContext::new(processes, Some(home.clone()));
+        let detections = detect_games(&context).unwrap();
+        assert_eq!(detected(detections.clone()), vec![(app_id, Some(10))]);
+        let DetectedGame::SteamShortcut { shortcut, .. } = &detections[0].game else {
+            unreachable!();
+        };
+        assert_eq!(shortcut.name, "Celeste");
+        assert_eq!(shortcut.exe, "/opt/celeste/Celeste");
+        assert_eq!(shortcut.user_id, 42);
+        assert!(SteamDetector::new().data_files(&context).contains(&config.join("shortcuts.vdf")));
+
+        std::fs::create_dir_all(home.join(".steam")).unwrap();
+        std::fs::write(home.join(".steam/registry.vdf"), format!(r#""Registry" {{ "HKCU" {{ "Software" {{ "Valve" {{ "Steam" {{ "RunningAppID" "{app_id}" }} }} }} }} }}"#)).unwrap();
+        let context = DetectContext::new(vec![], Some(home.clone()));
+        assert_eq!(detected(detect_games(&context).unwrap()), vec![(app_id, None)]);
+        std::fs::remove_dir_all(&home).unwrap();
+    }
+
+    #[test]
+    #[cfg(all(target_os = "linux", feature = "snapshot"))]
+    fn test_snapshot() {
+        use crate::{snapshot, DetectorRegistry};
+
+        let dir = std::env::temp_dir().join(format!("game-detect-steam-snapshot-{}", std::process::id()));
+        let home = dir.join("home");
+        let root = home.join(".local/share/Steam");
+        let proton = root.join("steamapps/common/Proton 10.0");
+        std::fs::create_dir_all(&proton).unwrap();
+        std::fs::create_dir_all(root.join("steamapps/compatdata/620")).unwrap();
+        std::fs::create_dir_all(root.join("config")).unwrap();
+        std::fs::write(proton.join("version"), "1750000000 proton-10.0-1\n").unwrap();
+        std::fs::write(root.join("steamapps/compatdata/620/version"), "10.0-100\n").unwrap();
+        std::fs::write(root.join("steamapps/appmanifest_620.acf"), r#""AppState" { "appid" "620" "name" "Portal 2" "installdir" "Portal 2" }"#).unwrap();
+        std::fs::write(root.join("steamapps/appmanifest_3658110.acf"), r#""AppState" { "appid" "3658110" "name" "Proton 10.0" "installdir" "Proton 10.0" }"#).unwrap();
+        std::fs::write(root.join("config/config.vdf"), r#""InstallConfigStore" { "Software" { "Valve" { "Steam" { "CompatToolMapping" { "620" { "name" "proton_10" } } } } } }"#).unwrap();
+        std::fs::write(root.join("config/loginusers.vdf"), r#""users" { "76561197960265770" { "AccountName" "alice" "MostRecent" "1" } }"#).unwrap();
+        for user_id in [1, 42] {
+            let grid = root.join(format!("userdata/{user_id}/config/grid"));
+            std::fs::create_dir_all(&grid).unwrap();
+            std::fs::write(grid.join("620_icon.png"), "").unwrap();
+            std::fs::write(grid.join("440p.png"), "").unwrap();
+        }
+
+        let processes = vec![
+            process(10, 1, &["/games/portal2/portal2_linux"], &[("SteamAppId", "620"), ("STEAM_COMPAT_TOOL_PATHS", &proton.to_string_lossy())]),
+            process(20, 1, &["/games/proton/proton"], &[("SteamAppId", "3658110")]),
+        ];
+        let context = DetectContext::new(processes, Some(home.clone()));
+        let mut registry = DetectorRegistry::empty();
+        registry.register(SteamDetector::new().exclude_non_games(true));
+        let expected = registry.detect_all_with(&context).unwrap();
+        let [Detection { game: DetectedGame::Steam { id: 620, icon, compatibility: Some(compatibility), .. }, .. }] = expected.as_slice() else {
+            panic!("unexpected detections {expected:?}");
+        };
+        assert_eq!(icon, &root.join("userdata/42/config/grid/620_icon.png").to_string_lossy());
+        assert_eq!(compatibility.tool.as_deref(), Some("proton_10"));
+        assert_eq!(compatibility.tool_version.as_deref(), Some("proton-10.0-1"));
+        assert_eq!(compatibility.prefix_version.as_deref(), Some("10.0-100"));
+
+        snapshot::capture_context(&registry, &context, dir.join("snapshot")).unwrap();
+        let files = dir.join("snapshot/files").join(crate::context::relative(&root));
+        assert!(files.join("userdata/42/config/grid/620_icon.png").is_file());
+        assert!(!files.join("userdata/42/config/grid/440p.png").exists());
+        std::fs::remove_dir_all(&home).unwrap();
+        let replayed = snapshot::load(dir.join("snapshot")).unwrap();
+        // Games compare by id alone, so compare everything detected.
+        assert_eq!(format!("{:?}", registry.detect_all_with(&replayed).unwrap()), format!("{expected:?}"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[actix_rt::test]
+    #[cfg(feature = "http")]
+    async fn test_fetch_info() {
+        let info = fetch_info(&DetectedGame::Steam {
+            id: 601050,
+            url: "https://store.steampowered.com/app/601050".to_string(),
+            icon: "https://cdn.cloudflare.steamstatic.com/steam/apps/601050/hero_capsule.jpg".to_string(),
+            compatibility: None,
+        }, &InfoOptions::default()).await.unwrap();
+        assert_eq!(info.name, "Attack on Titan 2 - A.O.T.2");
+        assert_eq!(info.via_platform, crate::GamePlatform::Steam);
+        assert_eq!(info.app_id, Some(601050));
+        assert_eq!(info.required_age, Some(15));
+        assert_eq!(info.url, "https://store.steampowered.com/app/601050");
+        assert_eq!(info.icon, "https://cdn.cloudflare.steamstatic.com/steam/apps/601050/hero_capsule.jpg".to_string());
+        // Omit the query parameters to guarantee the cover URL is static
+        assert_eq!(info.cover.split("?").collect::<Vec<&str>>()[0].to_owned(), "https://shared.akamai.steamstatic.com/store_item_assets/steam/apps/601050/page.bg.jpg".to_string());
+        assert_eq!(info.developers, vec!["KOEI TECMO GAMES CO., LTD.".to_owned()]);
+        assert_eq!(info.publishers, vec!["KOEI TECMO GAMES CO., LTD.".to_owned()]);
+        assert_eq!(info.description, "Abandon all fear. Attack on Titan 2 is the gripping sequel to the action game based on the worldwide hit anime series &quot;Attack on Titan.&quot;".to_string());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,103 @@
+use std::time::{Duration, SystemTime};
+
+use crate::{DetectContext, DetectError};
+use super::library::{installed_apps, InstalledApp};
+use super::users::active_userdata_dir;
+use super::vdf;
+
+/// How long and how recently the active Steam user played an app, from their
+/// `userdata/<account id>/config/localconfig.vdf`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AppPlaytime {
+    pub app_id: i64,
+    pub playtime: Duration,
+    pub playtime_two_weeks: Duration,
+    pub last_played: Option<SystemTime>,
+    /// The app's manifest, if it's still installed.
+    pub installed: Option<InstalledApp>,
+}
+
+/// Every app the active user has played, most recently played first. Empty if there is no active
+/// user.
+pub fn playtimes(context: &DetectContext) -> Result<Vec<AppPlaytime>, DetectError> {
+    let Some(userdata) = active_userdata_dir(context)? else {
+        return Ok(vec![]);
+    };
+    let path = context.path(userdata.join("config").join("localconfig.vdf"));
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let localconfig = vdf::read(&path)?;
+    let Some(apps) = vdf::lookup(&localconfig, &["UserLocalConfigStore", "Software", "Valve", "Steam", "apps"]).and_then(|apps| apps.as_table()) else {
+        return Ok(vec![]);
+    };
+    let mut installed = installed_apps(context)?;
+    let mut playtimes: Vec<AppPlaytime> = apps.iter()
+        .filter_map(|(app_id, app)| {
+            let app_id = app_id.parse::<i64>().ok()?;
+            let minutes = |key: &str| vdf::get_parsed::<u64>(app, key).map(|minutes| Duration::from_secs(minutes * 60));
+            let playtime = minutes("Playtime");
+            let last_played = vdf::get_parsed::<u64>(app, "LastPlayed")
+                .filter(|seconds| *seconds != 0)
+                .map(|seconds| SystemTime::UNIX_EPOCH + Duration::from_secs(seconds));
+            // Apps the user only has settings for, such as cloud sync choices, aren't played.
+            if playtime.is_none() && last_played.is_none() {
+                return None;
+            }
+            let installed = installed.iter().position(|installed| installed.app_id == app_id)
+                .map(|index| installed.swap_remove(index));
+            Some(AppPlaytime {
+                app_id,
+                playtime: playtime.unwrap_or_default(),
+                playtime_two_weeks: minutes("Playtime2wks").unwrap_or_default(),
+                last_played,
+                installed,
+            })
+        })
+        .collect();
+    playtimes.sort_by(|a, b| b.last_played.cmp(&a.last_played).then(a.app_id.cmp(&b.app_id)));
+    Ok(playtimes)
+}
+
+/// The active user's playtime for `app_id`, or `None` if they haven't played it.
+pub fn playtime(context: &DetectContext, app_id: i64) -> Result<Option<AppPlaytime>, DetectError> {
+    Ok(playtimes(context)?.into_iter().find(|playtime| playtime.app_id == app_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_playtimes() {
+        let home = std::env::temp_dir().join(format!("game-detect-steam-playtime-{}", std::process::id()));
+        let root = home.join(".local/share/Steam");
+        std::fs::create_dir_all(root.join("steamapps")).unwrap();
+        std::fs::create_dir_all(root.join("userdata/22202/config")).unwrap();
+        std::fs::write(root.join("steamapps/appmanifest_620.acf"), r#""AppState" { "appid" "620" "name" "Portal 2" "installdir" "Portal 2" }"#).unwrap();
+        let context = DetectContext::new(vec![], Some(home.clone()));
+        assert!(playtimes(&context).unwrap().is_empty());
+
+        std::fs::write(root.join("userdata/22202/config/localconfig.vdf"), r#""UserLocalConfigStore"
+{
+	"Software" { "Valve" { "Steam" { "Apps"
+	{
+		"400" { "LastPlayed" "1600000000" "Playtime" "90" }
+		"620" { "LastPlayed" "1700000000" "Playtime" "754" "Playtime2wks" "61" }
+		"730" { "cloud" { "last_sync_state" "synchronized" } }
+	} } } }
+}"#).unwrap();
+        let playtimes = playtimes(&context).unwrap();
+        assert_eq!(playtimes.iter().map(|playtime| playtime.app_id).collect::<Vec<_>>(), vec![620, 400]);
+        assert_eq!(playtimes[0].playtime, Duration::from_secs(754 * 60));
+        assert_eq!(playtimes[0].playtime_two_weeks, Duration::from_secs(61 * 60));
+        assert_eq!(playtimes[0].last_played, Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1700000000)));
+        assert_eq!(playtimes[0].installed.as_ref().map(|app| app.name.as_str()), Some("Portal 2"));
+        assert_eq!(playtimes[1].installed, None);
+        assert_eq!(playtime(&context, 400).unwrap().unwrap().playtime_two_weeks, Duration::ZERO);
+        assert_eq!(playtime(&context, 730).unwrap(), None);
+        std::fs::remove_dir_all(&home).unwrap();
+    }
+}
@@ -0,0 +1,199 @@
+use std::fs::read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::{DetectContext, DetectError, GameInfo, GamePlatform};
+use super::binary_vdf::{Reader, Value};
+use super::grid::{user_grid_artwork, GridArtwork};
+use super::library::userdata_dirs;
+
+/// A game added to Steam as a non-Steam game, read from `userdata/<user id>/config/shortcuts.vdf`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Shortcut {
+    /// The 32-bit app id Steam uses for the shortcut, as in `RunningAppID`.
+    pub app_id: i64,
+    /// The account id of the Steam user whose library the shortcut is in.
+    pub user_id: i64,
+    pub name: String,
+    /// The executable, without the quotes Steam stores it with.
+    pub exe: String,
+    pub start_dir: String,
+    pub launch_options: String,
+    /// A path to an image file, or empty if the shortcut uses the executable's icon.
+    pub icon: String,
+    pub tags: Vec<String>,
+    pub last_played: Option<SystemTime>,
+    pub artwork: GridArtwork,
+}
+
+impl Shortcut {
+    /// The 64-bit id Steam sets as `SteamGameId` and uses in `steam://rungameid/` links.
+    pub fn game_id(&self) -> u64 {
+        (self.app_id as u64) << 32 | 0x02000000
+    }
+
+    /// Custom grid artwork, if the user set any, takes precedence over the shortcut's icon.
+    pub fn to_game_info(&self) -> GameInfo {
+        let path = |path: &Path| path.to_string_lossy().into_owned();
+        GameInfo {
+            app_id: None,
+            via_platform: GamePlatform::Steam,
+            name: self.name.clone(),
+            description: String::new(),
+            cover: self.artwork.cover().map(path).unwrap_or_default(),
+            icon: self.artwork.icon.as_deref().map(path).unwrap_or_else(|| self.icon.clone()),
+            developers: vec![],
+            publishers: vec![],
+            required_age: None,
+            url: format!("steam://rungameid/{}", self.game_id()),
+        }
+    }
+}
+
+/// The app id Steam derives for a shortcut from its quoted executable and name. Newer clients
+/// store the id in the file instead, which is preferred when present.
+pub fn shortcut_app_id(exe: &str, name: &str) -> i64 {
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(exe.as_bytes());
+    hasher.update(name.as_bytes());
+    (hasher.finalize() | 0x80000000) as i64
+}
+
+/// Shortcut app ids always have the top bit set, which no store app id does.
+pub(crate) fn is_shortcut_app_id(app_id: i64) -> bool {
+    app_id >= 0x80000000 && app_id <= u32::MAX as i64
+}
+
+pub(crate) fn shortcut_files(context: &DetectContext) -> Result<Vec<(i64, PathBuf)>, DetectError> {
+    Ok(userdata_dirs(context)?.into_iter()
+        .map(|(user_id, dir)| (user_id, shortcuts_path(&dir)))
+        .collect())
+}
+
+fn shortcuts_path(userdata: &Path) -> PathBuf {
+    userdata.join("config").join("shortcuts.vdf")
+}
+
+/// Every shortcut of every Steam user on this machine. Files that can't be parsed are skipped.
+pub fn shortcuts(context: &DetectContext) -> Result<Vec<Shortcut>, DetectError> {
+    let mut shortcuts = vec![];
+    for (user_id, userdata) in userdata_dirs(context)? {
+        let path = context.path(shortcuts_path(&userdata));
+        if !path.exists() {
+            continue;
+        }
+        for mut shortcut in read_shortcuts(&path, user_id)? {
+            shortcut.artwork = user_grid_artwork(context, &userdata, shortcut.app_id, Some(shortcut.game_id()));
+            shortcuts.push(shortcut);
+        }
+    }
+    Ok(shortcuts)
+}
+
+fn read_shortcuts(path: &Path, user_id: i64) -> Result<Vec<Shortcut>, DetectError> {
+    let data = read(path).map_err(|e| DetectError::io(path, e))?;
+    let Ok(root) = Reader::new(&data).table() else {
+        return Ok(vec![]);
+    };
+    let entries = root.get("shortcuts").map(Value::entries).unwrap_or_default();
+    Ok(entries.iter().map(|(_, entry)| parse_shortcut(entry, user_id)).collect())
+}
+
+fn parse_shortcut(entry: &Value, user_id: i64) -> Shortcut {
+    let string = |key: &str| entry.get(key).and_then(Value::as_str).unwrap_or_default();
+    let name = string("AppName");
+    let exe = string("Exe");
+    let app_id = entry.get("appid").and_then(Value::as_int)
+        .map(|app_id| app_id as u32 as i64)
+        .filter(|app_id| *app_id != 0)
+        .unwrap_or_else(|| shortcut_app_id(exe, name));
+    Shortcut {
+        app_id,
+        user_id,
+        name: name.to_owned(),
+        exe: exe.trim_matches('"').to_owned(),
+        start_dir: string("StartDir").trim_matches('"').to_owned(),
+        launch_options: string("LaunchOptions").to_owned(),
+        icon: string("icon").trim_matches('"').to_owned(),
+        tags: entry.get("tags").map(Value::entries).unwrap_or_default().iter()
+            .filter_map(|(_, tag)| tag.as_str().map(str::to_owned))
+            .collect(),
+        last_played: entry.get("LastPlayTime").and_then(Value::as_int)
+            .filter(|seconds| *seconds > 0)
+            .map(|seconds| SystemTime::UNIX_EPOCH + Duration::from_secs(seconds as u64)),
+        artwork: GridArtwork::default(),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use super::super::binary_vdf::write;
+
+    pub(crate) fn shortcuts_file(entries: Vec<Vec<(&str, Value)>>) -> Vec<u8> {
+        let entries = entries.into_iter().enumerate()
+            .map(|(index, entry)| (index.to_string(), Value::Table(entry.into_iter().map(|(key, value)| (key.to_owned(), value)).collect())))
+            .collect();
+        write(&Value::Table(vec![("shortcuts".to_owned(), Value::Table(entries))]), None)
+    }
+
+    #[test]
+    fn test_shortcut_app_id() {
+        // Steam ROM Manager and other tools compute the same ids.
+        assert_eq!(shortcut_app_id("\"/usr/bin/celeste\"", "Celeste"), (crc32fast::hash(b"\"/usr/bin/celeste\"Celeste") | 0x80000000) as i64);
+        assert!(is_shortcut_app_id(shortcut_app_id("", "")));
+        assert!(!is_shortcut_app_id(620));
+    }
+
+    #[test]
+    fn test_read_shortcuts() {
+        let path = std::env::temp_dir().join(format!("game-detect-steam-shortcuts-{}.vdf", std::process::id()));
+        std::fs::write(&path, shortcuts_file(vec![
+            vec![
+                ("appid", Value::Int(-1234567890)),
+                ("AppName", Value::String("Celeste".to_owned())),
+                ("Exe", Value::String("\"/opt/celeste/Celeste\"".to_owned())),
+                ("StartDir", Value::String("\"/opt/celeste/\"".to_owned())),
+                ("icon", Value::String("/opt/celeste/icon.png".to_owned())),
+                ("LaunchOptions", Value::String("--fullscreen".to_owned())),
+                ("LastPlayTime", Value::Int(1700000000)),
+                ("tags", Value::Table(vec![("0".to_owned(), Value::String("favorite".to_owned()))])),
+            ],
+            vec![
+                ("appname", Value::String("Old".to_owned())),
+                ("exe", Value::String("\"old.exe\"".to_owned())),
+            ],
+        ])).unwrap();
+        let shortcuts = read_shortcuts(&path, 42).unwrap();
+        assert_eq!(shortcuts[0], Shortcut {
+            app_id: -1234567890i32 as u32 as i64,
+            user_id: 42,
+            name: "Celeste".to_owned(),
+            exe: "/opt/celeste/Celeste".to_owned(),
+            start_dir: "/opt/celeste/".to_owned(),
+            launch_options: "--fullscreen".to_owned(),
+            icon: "/opt/celeste/icon.png".to_owned(),
+            tags: vec!["favorite".to_owned()],
+            last_played: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1700000000)),
+            artwork: GridArtwork::default(),
+        });
+        assert_eq!(shortcuts[1].app_id, shortcut_app_id("\"old.exe\"", "Old"));
+        assert_eq!(shortcuts[1].exe, "old.exe");
+
+        let info = shortcuts[0].to_game_info();
+        assert_eq!(info.name, "Celeste");
+        assert_eq!(info.app_id, None);
+        assert_eq!(info.url, format!("steam://rungameid/{}", (3060399406u64 << 32) | 0x02000000));
+        assert_eq!(info.icon, "/opt/celeste/icon.png");
+        let mut shortcut = shortcuts[0].clone();
+        shortcut.artwork = GridArtwork { icon: Some(PathBuf::from("/grid/3060399406_icon.png")), hero: Some(PathBuf::from("/grid/3060399406_hero.png")), ..Default::default() };
+        let info = shortcut.to_game_info();
+        assert_eq!(info.icon, "/grid/3060399406_icon.png");
+        assert_eq!(info.cover, "/grid/3060399406_hero.png");
+
+        std::fs::write(&path, b"\x00shortcuts").unwrap();
+        assert!(read_shortcuts(&path, 42).unwrap().is_empty());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
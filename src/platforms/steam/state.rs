@@ -0,0 +1,147 @@
+use crate::{DetectContext, DetectError};
+use super::library::installed_app;
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+use super::vdf;
+use super::{client_running, registry_running_app_id, running_apps};
+
+// Bits of a manifest's `StateFlags`.
+const APP_RUNNING: u32 = 1 << 6;
+const UPDATE_RUNNING: u32 = 1 << 8;
+const UPDATE_STARTED: u32 = 1 << 10;
+const VALIDATING: u32 = 1 << 17;
+const ADDING_FILES: u32 = 1 << 18;
+const PREALLOCATING: u32 = 1 << 19;
+const DOWNLOADING: u32 = 1 << 20;
+const STAGING: u32 = 1 << 21;
+const COMMITTING: u32 = 1 << 22;
+const UPDATING: u32 = UPDATE_RUNNING | UPDATE_STARTED | VALIDATING | ADDING_FILES | PREALLOCATING | DOWNLOADING | STAGING | COMMITTING;
+
+/// What Steam is doing with an app.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum AppState {
+    NotInstalled,
+    Installed,
+    /// Being installed, updated or verified.
+    Updating { bytes_downloaded: u64, bytes_to_download: u64 },
+    /// Steam has started the app but none of its processes are running yet.
+    Launching,
+    Running,
+}
+
+impl AppState {
+    /// How far along an update is, from 0 to 1, if the download size is known.
+    pub fn progress(&self) -> Option<f32> {
+        match self {
+            AppState::Updating { bytes_downloaded, bytes_to_download } if *bytes_to_download > 0 => {
+                Some((*bytes_downloaded as f64 / *bytes_to_download as f64).min(1.0) as f32)
+            }
+            _ => None,
+        }
+    }
+}
+
+// The `Updating` and `Running` values Steam keeps for each app in its registry.
+#[derive(Default)]
+struct RegistryApp {
+    updating: bool,
+    running: bool,
+}
+
+/// The state of `app_id`, from the running processes, Steam's registry and the app's manifest.
+/// Steam marks an app as running as soon as it is launched, so until a process of the app shows
+/// up it is reported as launching. As with detection, Steam's word is only taken while the client
+/// is running, or when there is no process list to check it against.
+pub fn app_state(context: &DetectContext, app_id: i64) -> Result<AppState, DetectError> {
+    if running_apps(context).iter().any(|(id, _)| *id == app_id) {
+        return Ok(AppState::Running);
+    }
+    let registry = registry_app(context, app_id)?;
+    let manifest = installed_app(context, app_id)?;
+    let flags = manifest.as_ref().map(|manifest| manifest.state_flags).unwrap_or_default();
+    if registry.running || flags & APP_RUNNING != 0 || registry_running_app_id(context)? == Some(app_id) {
+        if context.processes().is_empty() {
+            return Ok(AppState::Running);
+        }
+        if client_running(context) {
+            return Ok(AppState::Launching);
+        }
+    }
+    let Some(manifest) = manifest else {
+        return Ok(AppState::NotInstalled);
+    };
+    // Paused downloads keep their folder, so it only counts while bytes are outstanding.
+    let downloading = manifest.bytes_downloaded < manifest.bytes_to_download
+        && context.path(manifest.library.join("steamapps").join("downloading").join(app_id.to_string())).is_dir();
+    if flags & UPDATING != 0 || registry.updating || downloading {
+        return Ok(AppState::Updating { bytes_downloaded: manifest.bytes_downloaded, bytes_to_download: manifest.bytes_to_download });
+    }
+    Ok(AppState::Installed)
+}
+
+#[cfg_attr(target_family = "windows", allow(unused_variables))]
+fn registry_app(context: &DetectContext, app_id: i64) -> Result<RegistryApp, DetectError> {
+    #[cfg(target_family = "windows")]
+    {
+        use registry::{Data, Hive, Security};
+        let Ok(key) = Hive::CurrentUser.open(format!(r"{}\Apps\{app_id}", super::REG_TREE_PATH).as_str(), Security::Read) else {
+            return Ok(RegistryApp::default());
+        };
+        let flag = |name: &str| matches!(key.value(name), Ok(Data::U32(value)) if value != 0);
+        Ok(RegistryApp { updating: flag("Updating"), running: flag("Running") })
+    }
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    {
+        for registry_path in super::registry_paths(context)? {
+            let registry_path = context.path(registry_path);
+            if !registry_path.exists() {
+                continue;
+            }
+            let registry = vdf::read(&registry_path)?;
+            let Some(app) = vdf::lookup(&registry, &["Registry", "HKCU", "Software", "Valve", "Steam", "apps", &app_id.to_string()]) else {
+                continue;
+            };
+            let flag = |name: &str| vdf::get_parsed::<u32>(app, name).is_some_and(|value| value != 0);
+            return Ok(RegistryApp { updating: flag("Updating"), running: flag("Running") });
+        }
+        Ok(RegistryApp::default())
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::testing::{create_steam_root, launched, TempDir};
+    use std::fs::{create_dir_all, write};
+
+    #[test]
+    fn test_app_state() {
+        let home = TempDir::new("steam-state");
+        let steamapps = create_steam_root(&home).join("steamapps");
+        create_dir_all(steamapps.join("downloading/400")).unwrap();
+        create_dir_all(home.join(".steam")).unwrap();
+        let manifest = |app_id: i64, flags: u32, downloaded: u64| format!(r#""AppState" {{ "appid" "{app_id}" "name" "App" "installdir" "App" "StateFlags" "{flags}" "BytesDownloaded" "{downloaded}" "BytesToDownload" "400" }}"#);
+        write(steamapps.join("appmanifest_620.acf"), manifest(620, 4, 400)).unwrap();
+        write(steamapps.join("appmanifest_1245620.acf"), manifest(1245620, 4 | UPDATE_RUNNING | DOWNLOADING, 100)).unwrap();
+        write(steamapps.join("appmanifest_400.acf"), manifest(400, 6, 300)).unwrap();
+        write(steamapps.join("appmanifest_440.acf"), manifest(440, 4, 0)).unwrap();
+        write(home.join(".steam/registry.vdf"), r#""Registry" { "HKCU" { "Software" { "Valve" { "Steam" { "apps" { "440" { "Running" "1" "Updating" "0" } } } } } } }"#).unwrap();
+
+        let steam = launched(1, 0, &["/home/user/.local/share/Steam/ubuntu12_32/steam"], &[]);
+        let game = launched(2, 0, &["/games/portal2/portal2_linux"], &[("SteamAppId", "620")]);
+        let context = DetectContext::new(vec![steam.clone(), game], Some(home.to_path_buf()));
+        assert_eq!(app_state(&context, 620).unwrap(), AppState::Running);
+        let updating = app_state(&context, 1245620).unwrap();
+        assert_eq!(updating, AppState::Updating { bytes_downloaded: 100, bytes_to_download: 400 });
+        assert_eq!(updating.progress(), Some(0.25));
+        assert_eq!(app_state(&context, 400).unwrap().progress(), Some(0.75));
+        assert_eq!(app_state(&context, 440).unwrap(), AppState::Launching);
+        assert_eq!(app_state(&context, 570).unwrap(), AppState::NotInstalled);
+
+        let context = DetectContext::new(vec![steam], Some(home.to_path_buf()));
+        assert_eq!(app_state(&context, 620).unwrap(), AppState::Installed);
+        // Without the client, Steam's running flag is stale.
+        let context = DetectContext::new(vec![launched(3, 0, &["/usr/bin/vim"], &[])], Some(home.to_path_buf()));
+        assert_eq!(app_state(&context, 440).unwrap(), AppState::Installed);
+    }
+}
@@ -0,0 +1,285 @@
+use crate::{FetchError, GameInfo, GamePlatform, InfoOptions};
+
+mod http {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    pub(crate) struct App {
+        pub name: String,
+        pub short_description: String,
+        // Usually a string, but the API returns a bare number for some apps.
+        pub required_age: serde_json::Value,
+        #[serde(default)]
+        pub developers: Vec<String>,
+        #[serde(default)]
+        pub publishers: Vec<String>,
+        pub background_raw: String,
+        #[serde(default)]
+        pub genres: Vec<Tag>,
+        #[serde(default)]
+        pub categories: Vec<Tag>,
+        pub release_date: Option<ReleaseDate>,
+        #[serde(default)]
+        pub platforms: Platforms,
+        pub metacritic: Option<Metacritic>,
+        pub website: Option<String>,
+        #[serde(default)]
+        pub screenshots: Vec<Screenshot>,
+    }
+
+    #[derive(Deserialize)]
+    pub(crate) struct Tag {
+        // A string for genres and a number for categories.
+        pub id: serde_json::Value,
+        pub description: String,
+    }
+
+    #[derive(Deserialize)]
+    pub(crate) struct ReleaseDate {
+        #[serde(default)]
+        pub coming_soon: bool,
+        #[serde(default)]
+        pub date: String,
+    }
+
+    #[derive(Deserialize, Default)]
+    pub(crate) struct Platforms {
+        #[serde(default)]
+        pub windows: bool,
+        #[serde(default)]
+        pub mac: bool,
+        #[serde(default)]
+        pub linux: bool,
+    }
+
+    #[derive(Deserialize)]
+    pub(crate) struct Metacritic {
+        pub score: i32,
+        #[serde(default)]
+        pub url: String,
+    }
+
+    #[derive(Deserialize)]
+    pub(crate) struct Screenshot {
+        pub path_thumbnail: String,
+        pub path_full: String,
+    }
+
+    #[derive(Deserialize)]
+    pub(crate) struct ResponseInner {
+        pub success: bool,
+        pub data: Option<App>
+    }
+
+    pub(crate) type Response = HashMap<i32, ResponseInner>;
+}
+
+/// Everything the store's `appdetails` API has on an app. The common parts are in `info`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StoreDetails {
+    pub info: GameInfo,
+    pub genres: Vec<StoreTag>,
+    /// Features such as single-player, online co-op or full controller support.
+    pub categories: Vec<StoreTag>,
+    /// As the store shows it, such as `13 Mar, 2018` or `Coming soon`.
+    pub release_date: Option<String>,
+    pub coming_soon: bool,
+    pub platforms: StorePlatforms,
+    pub metacritic: Option<Metacritic>,
+    pub website: Option<String>,
+    pub screenshots: Vec<Screenshot>,
+}
+
+/// A genre or category. Ids are the same in every language, descriptions are not.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StoreTag {
+    pub id: i64,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StorePlatforms {
+    pub windows: bool,
+    pub mac: bool,
+    pub linux: bool,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Metacritic {
+    pub score: i32,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Screenshot {
+    pub thumbnail: String,
+    pub full: String,
+}
+
+// The store's names for the languages it supports, keyed by BCP 47 tag.
+const LANGUAGES: [(&str, &str); 30] = [
+    ("ar", "arabic"), ("bg", "bulgarian"), ("zh-cn", "schinese"), ("zh-tw", "tchinese"), ("cs", "czech"),
+    ("da", "danish"), ("nl", "dutch"), ("en", "english"), ("fi", "finnish"), ("fr", "french"),
+    ("de", "german"), ("el", "greek"), ("hu", "hungarian"), ("id", "indonesian"), ("it", "italian"),
+    ("ja", "japanese"), ("ko", "koreana"), ("no", "norwegian"), ("pl", "polish"), ("pt", "portuguese"),
+    ("pt-br", "brazilian"), ("ro", "romanian"), ("ru", "russian"), ("es", "spanish"), ("es-419", "latam"),
+    ("sv", "swedish"), ("th", "thai"), ("tr", "turkish"), ("uk", "ukrainian"), ("vi", "vietnamese"),
+];
+
+/// The store's name for a language tag, trying the tag without its region if the store doesn't
+/// have that variant. Tags the store doesn't know are passed on as they are, so its own names,
+/// such as `german`, work too.
+fn store_language(language: &str) -> String {
+    let language = language.replace('_', "-").to_lowercase();
+    let base = language.split('-').next().unwrap_or_default();
+    let base = match base {
+        // Chinese without a region defaults to simplified, Norwegian Bokmål and Nynorsk to Norwegian.
+        "zh" if language.contains("hant") || language.ends_with("-hk") || language.ends_with("-mo") => "zh-tw",
+        "zh" => "zh-cn",
+        "nb" | "nn" => "no",
+        base => base,
+    };
+    LANGUAGES.iter()
+        .find(|(tag, _)| *tag == language)
+        .or_else(|| LANGUAGES.iter().find(|(tag, _)| *tag == base))
+        .map(|(_, name)| name.to_string())
+        .unwrap_or(language)
+}
+
+const APP_DETAILS_URL: &str = "https://store.steampowered.com/api/appdetails";
+
+// Options are escaped by the query builder, so they can't add parameters of their own.
+fn store_query(app_id: i64, options: &InfoOptions) -> Vec<(&'static str, String)> {
+    let mut query = vec![("appids", app_id.to_string())];
+    if let Some(language) = options.language.as_deref().filter(|language| !language.is_empty()) {
+        query.push(("l", store_language(language)));
+    }
+    if let Some(country) = options.country.as_deref().filter(|country| !country.is_empty()) {
+        query.push(("cc", country.to_uppercase()));
+    }
+    query
+}
+
+/// Fetches `app_id` from the store's `appdetails` API, in the language and region `options` ask
+/// for.
+pub async fn store_details(app_id: i64, options: &InfoOptions) -> Result<StoreDetails, FetchError> {
+    let client = reqwest::Client::new();
+    let response = client.get(APP_DETAILS_URL).query(&store_query(app_id, options)).send().await?;
+    if !response.status().is_success() {
+        return Err(FetchError::Status(response.status()));
+    }
+    parse(app_id, &response.text().await?)
+}
+
+fn parse(app_id: i64, response: &str) -> Result<StoreDetails, FetchError> {
+    let mut response: http::Response = serde_json::from_str(response)?;
+    let app = match response.remove(&(app_id as i32)) {
+        Some(http::ResponseInner { success: true, data: Some(app) }) => app,
+        _ => return Err(FetchError::NotFound)
+    };
+    let required_age = match &app.required_age {
+        serde_json::Value::Number(age) => age.as_i64().map(|age| age as i32),
+        serde_json::Value::String(age) => age.parse::<i32>().ok(),
+        _ => None
+    };
+    let tags = |tags: Vec<http::Tag>| tags.into_iter()
+        .filter_map(|tag| {
+            let id = match tag.id {
+                serde_json::Value::Number(id) => id.as_i64(),
+                serde_json::Value::String(id) => id.parse::<i64>().ok(),
+                _ => None
+            }?;
+            Some(StoreTag { id, description: tag.description })
+        })
+        .collect();
+    Ok(StoreDetails {
+        info: GameInfo {
+            cover: app.background_raw,
+            icon: format!("https://cdn.cloudflare.steamstatic.com/steam/apps/{0}/hero_capsule.jpg", app_id),
+            name: app.name,
+            via_platform: GamePlatform::Steam,
+            description: app.short_description,
+            developers: app.developers,
+            publishers: app.publishers,
+            app_id: Some(app_id),
+            required_age,
+            url: format!("https://store.steampowered.com/app/{0}", app_id),
+        },
+        genres: tags(app.genres),
+        categories: tags(app.categories),
+        coming_soon: app.release_date.as_ref().is_some_and(|date| date.coming_soon),
+        release_date: app.release_date.map(|date| date.date).filter(|date| !date.is_empty()),
+        platforms: StorePlatforms { windows: app.platforms.windows, mac: app.platforms.mac, linux: app.platforms.linux },
+        metacritic: app.metacritic.map(|metacritic| Metacritic { score: metacritic.score, url: metacritic.url }),
+        website: app.website.filter(|website| !website.is_empty()),
+        screenshots: app.screenshots.into_iter()
+            .map(|screenshot| Screenshot { thumbnail: screenshot.path_thumbnail, full: screenshot.path_full })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let details = parse(620, r#"{"620":{"success":true,"data":{
+            "type":"game","name":"Portal 2","steam_appid":620,"required_age":0,
+            "short_description":"Sequel","developers":["Valve"],"publishers":["Valve"],
+            "background_raw":"https://example.com/bg.jpg","website":"http://www.thinkwithportals.com/",
+            "platforms":{"windows":true,"mac":false,"linux":true},
+            "metacritic":{"score":95,"url":"https://www.metacritic.com/game/pc/portal-2"},
+            "categories":[{"id":2,"description":"Single-player"},{"id":28,"description":"Full controller support"}],
+            "genres":[{"id":"1","description":"Action"},{"id":"25","description":"Adventure"}],
+            "screenshots":[{"id":0,"path_thumbnail":"https://example.com/0.600x338.jpg","path_full":"https://example.com/0.1920x1080.jpg"}],
+            "release_date":{"coming_soon":false,"date":"18 Apr, 2011"}
+        }}}"#).unwrap();
+        assert_eq!(details.info.name, "Portal 2");
+        assert_eq!(details.info.required_age, Some(0));
+        assert_eq!(details.info.url, "https://store.steampowered.com/app/620");
+        assert_eq!(details.genres, vec![
+            StoreTag { id: 1, description: "Action".to_owned() },
+            StoreTag { id: 25, description: "Adventure".to_owned() },
+        ]);
+        assert_eq!(details.categories.iter().map(|category| category.id).collect::<Vec<_>>(), vec![2, 28]);
+        assert_eq!(details.release_date.as_deref(), Some("18 Apr, 2011"));
+        assert!(!details.coming_soon);
+        assert_eq!(details.platforms, StorePlatforms { windows: true, mac: false, linux: true });
+        assert_eq!(details.metacritic.map(|metacritic| metacritic.score), Some(95));
+        assert_eq!(details.website.as_deref(), Some("http://www.thinkwithportals.com/"));
+        assert_eq!(details.screenshots[0].full, "https://example.com/0.1920x1080.jpg");
+
+        let details = parse(1, r#"{"1":{"success":true,"data":{"name":"Soon","required_age":"18","short_description":"","background_raw":"","website":null,"release_date":{"coming_soon":true,"date":""}}}}"#).unwrap();
+        assert_eq!(details.info.required_age, Some(18));
+        assert!(details.coming_soon);
+        assert_eq!(details.release_date, None);
+        assert_eq!(details.website, None);
+        assert!(details.genres.is_empty());
+        assert!(matches!(parse(2, r#"{"2":{"success":false}}"#), Err(FetchError::NotFound)));
+    }
+
+    #[test]
+    fn test_store_query() {
+        let url = |options: &InfoOptions| reqwest::Client::new().get(APP_DETAILS_URL).query(&store_query(620, options)).build().unwrap().url().to_string();
+        assert_eq!(url(&InfoOptions::default()), "https://store.steampowered.com/api/appdetails?appids=620");
+        let options = InfoOptions { language: Some("de-AT".to_owned()), country: Some("at".to_owned()), ..Default::default() };
+        assert_eq!(url(&options), "https://store.steampowered.com/api/appdetails?appids=620&l=german&cc=AT");
+        let options = InfoOptions { language: Some("en&cc=us".to_owned()), country: Some("de&l=x".to_owned()), ..Default::default() };
+        assert_eq!(url(&options), "https://store.steampowered.com/api/appdetails?appids=620&l=en%26cc%3Dus&cc=DE%26L%3DX");
+        assert_eq!(store_language("pt-BR"), "brazilian");
+        assert_eq!(store_language("pt_PT"), "portuguese");
+        assert_eq!(store_language("zh-Hant-TW"), "tchinese");
+        assert_eq!(store_language("zh"), "schinese");
+        assert_eq!(store_language("es-MX"), "spanish");
+        assert_eq!(store_language("es-419"), "latam");
+        assert_eq!(store_language("german"), "german");
+    }
+}
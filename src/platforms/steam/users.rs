@@ -0,0 +1,229 @@
+use std::path::PathBuf;
+
+use crate::{DetectContext, DetectError};
+use super::library::{steam_roots, userdata_dirs};
+use super::vdf;
+
+// A SteamID64 of an individual account is this plus the 32-bit account id.
+const STEAM_ID64_BASE: u64 = 76561197960265728;
+
+// An entry in `config/loginusers.vdf`.
+struct LoginUser {
+    steam_id: u64,
+    #[cfg_attr(not(feature = "steam-account"), allow(dead_code))]
+    account_name: String,
+    #[cfg_attr(not(feature = "steam-account"), allow(dead_code))]
+    persona_name: String,
+    most_recent: bool,
+    timestamp: u64,
+}
+
+impl LoginUser {
+    fn account_id(&self) -> i64 {
+        (self.steam_id - STEAM_ID64_BASE) as i64
+    }
+}
+
+// The users of the first root that has any.
+fn login_users(context: &DetectContext) -> Result<Vec<LoginUser>, DetectError> {
+    for root in steam_roots(context)? {
+        let path = context.path(root.join("config").join("loginusers.vdf"));
+        if !path.exists() {
+            continue;
+        }
+        let loginusers = vdf::read(&path)?;
+        let Some(users) = vdf::get(&loginusers, "users").and_then(|users| users.as_table()) else {
+            continue;
+        };
+        let users: Vec<LoginUser> = users.iter()
+            .filter_map(|(steam_id, user)| Some(LoginUser {
+                steam_id: steam_id.parse::<u64>().ok().filter(|steam_id| *steam_id > STEAM_ID64_BASE)?,
+                account_name: vdf::get_str(user, "AccountName").unwrap_or_default().to_owned(),
+                persona_name: vdf::get_str(user, "PersonaName").unwrap_or_default().to_owned(),
+                most_recent: vdf::get_str(user, "MostRecent") == Some("1"),
+                timestamp: vdf::get_parsed::<u64>(user, "Timestamp").unwrap_or_default(),
+            }))
+            .collect();
+        if !users.is_empty() {
+            return Ok(users);
+        }
+    }
+    Ok(vec![])
+}
+
+fn most_recent(users: &[LoginUser]) -> Option<&LoginUser> {
+    users.iter().max_by_key(|user| (user.most_recent, user.timestamp, user.steam_id))
+}
+
+/// The account id of the user who signed in to Steam last, which names their `userdata` folder.
+/// Taken from the `MostRecent` entry in `config/loginusers.vdf`, or the newest `Timestamp` if no
+/// entry has it. Without the file, a lone `userdata` folder is assumed to be the user's.
+pub fn active_user(context: &DetectContext) -> Result<Option<i64>, DetectError> {
+    if let Some(user) = most_recent(&login_users(context)?) {
+        return Ok(Some(user.account_id()));
+    }
+    let dirs = userdata_dirs(context)?;
+    Ok(match dirs.as_slice() {
+        [(user_id, _)] => Some(*user_id),
+        _ => None,
+    })
+}
+
+/// The active user's `userdata/<account id>` folder.
+pub(crate) fn active_userdata_dir(context: &DetectContext) -> Result<Option<PathBuf>, DetectError> {
+    let Some(user_id) = active_user(context)? else {
+        return Ok(None);
+    };
+    Ok(userdata_dirs(context)?.into_iter().find(|(id, _)| *id == user_id).map(|(_, dir)| dir))
+}
+
+/// The Steam account signed in on this machine. Only available with the `steam-account` feature,
+/// as it identifies the user.
+#[cfg(feature = "steam-account")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SteamAccount {
+    pub steam_id: u64,
+    /// The 32-bit id that names the account's `userdata` folder.
+    pub account_id: i64,
+    /// The name the user signs in with.
+    pub account_name: String,
+    /// The name other users see.
+    pub persona_name: String,
+}
+
+/// The account Steam is signed in to, from `ActiveUser` while the client is running. When nobody
+/// is signed in, the `AutoLoginUser` it will sign in as, then the most recent entry in
+/// `config/loginusers.vdf`. `None` if the account isn't listed in `loginusers.vdf`.
+#[cfg(feature = "steam-account")]
+pub fn active_account(context: &DetectContext) -> Result<Option<SteamAccount>, DetectError> {
+    let users = login_users(context)?;
+    let (active_user, auto_login_user) = registry_users(context)?;
+    // Falling back while someone is signed in could name another member of the household.
+    let user = match active_user {
+        Some(account_id) => users.iter().find(|user| user.account_id() == account_id),
+        None => auto_login_user
+            .and_then(|auto_login_user| users.iter().find(|user| user.account_name.eq_ignore_ascii_case(&auto_login_user)))
+            .or_else(|| most_recent(&users)),
+    };
+    Ok(user.map(|user| SteamAccount {
+        steam_id: user.steam_id,
+        account_id: user.account_id(),
+        account_name: user.account_name.clone(),
+        persona_name: user.persona_name.clone(),
+    }))
+}
+
+// `ActiveUser` is the signed in account id, 0 while nobody is. `AutoLoginUser` is an account name.
+#[cfg(feature = "steam-account")]
+#[cfg_attr(target_family = "windows", allow(unused_variables))]
+fn registry_users(context: &DetectContext) -> Result<(Option<i64>, Option<String>), DetectError> {
+    #[cfg(target_family = "windows")]
+    {
+        use registry::{Data, Hive, Security};
+        let active_user = Hive::CurrentUser.open(format!(r"{}\ActiveProcess", super::REG_TREE_PATH).as_str(), Security::Read).ok()
+            .and_then(|key| match key.value("ActiveUser") {
+                Ok(Data::U32(account_id)) => Some(account_id as i64),
+                _ => None,
+            });
+        let auto_login_user = Hive::CurrentUser.open(super::REG_TREE_PATH, Security::Read).ok()
+            .and_then(|key| match key.value("AutoLoginUser") {
+                Ok(Data::String(name)) => Some(name.to_string_lossy()),
+                _ => None,
+            });
+        Ok((active_user.filter(|account_id| *account_id != 0), auto_login_user.filter(|name| !name.is_empty())))
+    }
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    {
+        for registry_path in super::registry_paths(context)? {
+            let registry_path = context.path(registry_path);
+            if !registry_path.exists() {
+                continue;
+            }
+            let registry = vdf::read(&registry_path)?;
+            let Some(steam) = vdf::lookup(&registry, &["Registry", "HKCU", "Software", "Valve", "Steam"]) else {
+                continue;
+            };
+            let active_user = vdf::lookup(steam, &["ActiveProcess", "ActiveUser"])
+                .and_then(|account_id| account_id.as_str())
+                .and_then(|account_id| account_id.parse::<i64>().ok())
+                .filter(|account_id| *account_id != 0);
+            let auto_login_user = vdf::get_str(steam, "AutoLoginUser").filter(|name| !name.is_empty()).map(str::to_owned);
+            if active_user.is_some() || auto_login_user.is_some() {
+                return Ok((active_user, auto_login_user));
+            }
+        }
+        Ok((None, None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_active_user() {
+        let home = std::env::temp_dir().join(format!("game-detect-steam-users-{}", std::process::id()));
+        let root = home.join(".local/share/Steam");
+        std::fs::create_dir_all(root.join("userdata/22202")).unwrap();
+        let context = DetectContext::new(vec![], Some(home.clone()));
+        assert_eq!(active_user(&context).unwrap(), Some(22202));
+
+        std::fs::create_dir_all(root.join("userdata/1")).unwrap();
+        assert_eq!(active_user(&context).unwrap(), None);
+
+        std::fs::create_dir_all(root.join("config")).unwrap();
+        std::fs::write(root.join("config/loginusers.vdf"), r#""users"
+{
+	"76561197960287930" { "AccountName" "gaben" "MostRecent" "0" "Timestamp" "1700000000" }
+	"76561197960265729" { "AccountName" "other" "MostRecent" "1" "Timestamp" "1600000000" }
+}"#).unwrap();
+        assert_eq!(active_user(&context).unwrap(), Some(1));
+        assert_eq!(active_userdata_dir(&context).unwrap(), Some(root.join("userdata/1")));
+
+        std::fs::write(root.join("config/loginusers.vdf"), r#""users"
+{
+	"76561197960287930" { "AccountName" "gaben" "Timestamp" "1700000000" }
+	"76561197960265729" { "AccountName" "other" "Timestamp" "1600000000" }
+}"#).unwrap();
+        assert_eq!(active_user(&context).unwrap(), Some(22202));
+        std::fs::remove_dir_all(&home).unwrap();
+    }
+
+    #[test]
+    #[cfg(all(feature = "steam-account", target_os = "linux"))]
+    fn test_active_account() {
+        let home = std::env::temp_dir().join(format!("game-detect-steam-account-{}", std::process::id()));
+        let root = home.join(".local/share/Steam");
+        std::fs::create_dir_all(root.join("config")).unwrap();
+        std::fs::create_dir_all(root.join("userdata")).unwrap();
+        std::fs::create_dir_all(home.join(".steam")).unwrap();
+        let context = DetectContext::new(vec![], Some(home.clone()));
+        assert_eq!(active_account(&context).unwrap(), None);
+
+        std::fs::write(root.join("config/loginusers.vdf"), r#""users"
+{
+	"76561197960287930" { "AccountName" "gaben" "PersonaName" "Gabe" "MostRecent" "1" "Timestamp" "1700000000" }
+	"76561197960265729" { "AccountName" "other" "PersonaName" "Other" "MostRecent" "0" "Timestamp" "1600000000" }
+	"76561197960265730" { "AccountName" "third" "PersonaName" "Third" "MostRecent" "0" "Timestamp" "1500000000" }
+}"#).unwrap();
+        let account = active_account(&context).unwrap().unwrap();
+        assert_eq!(account, SteamAccount {
+            steam_id: 76561197960287930,
+            account_id: 22202,
+            account_name: "gaben".to_owned(),
+            persona_name: "Gabe".to_owned(),
+        });
+
+        let registry = |active_user: u32| format!(r#""Registry" {{ "HKCU" {{ "Software" {{ "Valve" {{ "Steam" {{ "AutoLoginUser" "Third" "ActiveProcess" {{ "ActiveUser" "{active_user}" }} }} }} }} }} }}"#);
+        std::fs::write(home.join(".steam/registry.vdf"), registry(0)).unwrap();
+        assert_eq!(active_account(&context).unwrap().unwrap().account_name, "third");
+        std::fs::write(home.join(".steam/registry.vdf"), registry(1)).unwrap();
+        assert_eq!(active_account(&context).unwrap().unwrap().persona_name, "Other");
+        // A signed in account missing from loginusers.vdf isn't mistaken for another.
+        std::fs::write(home.join(".steam/registry.vdf"), registry(5)).unwrap();
+        assert_eq!(active_account(&context).unwrap(), None);
+        std::fs::remove_dir_all(&home).unwrap();
+    }
+}
@@ -0,0 +1,94 @@
+use std::fs::read_to_string;
+use std::path::Path;
+
+use vdf_reader::entry::{Entry, Table};
+
+use crate::DetectError;
+
+pub fn read(path: &Path) -> Result<Entry, DetectError> {
+    let content = read_to_string(path).map_err(|e| DetectError::io(path, e))?;
+    parse(&content).map_err(|e| DetectError::parse(path, e))
+}
+
+pub fn parse(content: &str) -> Result<Entry, String> {
+    check_depth(content)?;
+    Table::load_from_str(content).map(Entry::Table).map_err(|e| e.to_string())
+}
+
+// The parser recurses once per level, so crafted files could overflow the stack. Real files nest a
+// handful of levels, like binary ones.
+const MAX_DEPTH: usize = 64;
+
+// Counts braces outside of quoted strings and comments.
+fn check_depth(content: &str) -> Result<(), String> {
+    let mut depth = 0usize;
+    let mut bytes = content.bytes().enumerate();
+    while let Some((offset, byte)) = bytes.next() {
+        match byte {
+            b'"' => {
+                while let Some((_, byte)) = bytes.next() {
+                    match byte {
+                        b'\\' => {
+                            bytes.next();
+                        }
+                        b'"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            b'/' if content.as_bytes().get(offset + 1) == Some(&b'/') => {
+                for (_, byte) in bytes.by_ref() {
+                    if byte == b'\n' {
+                        break;
+                    }
+                }
+            }
+            b'{' => {
+                depth += 1;
+                if depth > MAX_DEPTH {
+                    return Err(format!("nesting too deep at byte {offset}"));
+                }
+            }
+            b'}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+// Steam isn't consistent about key casing ("RunningAppID" vs "RunningAppId"), so lookups ignore it.
+pub fn get<'a>(entry: &'a Entry, key: &str) -> Option<&'a Entry> {
+    entry.as_table()?
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(key))
+        .map(|(_, value)| value)
+}
+
+pub fn lookup<'a>(entry: &'a Entry, path: &[&str]) -> Option<&'a Entry> {
+    path.iter().try_fold(entry, |entry, key| get(entry, key))
+}
+
+pub fn get_str<'a>(entry: &'a Entry, key: &str) -> Option<&'a str> {
+    get(entry, key)?.as_str()
+}
+
+pub fn get_parsed<T: std::str::FromStr>(entry: &Entry, key: &str) -> Option<T> {
+    get_str(entry, key)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let entry = parse(r#""AppState" { "name" "{ \"quoted\" }" // {{{
+"installdir" "Portal 2" }"#).unwrap();
+        assert_eq!(lookup(&entry, &["AppState", "installdir"]).and_then(Entry::as_str), Some("Portal 2"));
+
+        let nested = format!("{}{}", "\"a\" {".repeat(MAX_DEPTH), "}".repeat(MAX_DEPTH));
+        assert!(parse(&nested).is_ok());
+        let deep = "\"a\" {".repeat(100_000);
+        assert_eq!(parse(&deep).unwrap_err(), format!("nesting too deep at byte {}", 5 * MAX_DEPTH + 4));
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use procfs::process::{Process, Stat};
+
+/// A point-in-time description of the process a game was detected in.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProcessInfo {
+    pub pid: i32,
+    pub ppid: Option<i32>,
+    pub exe: Option<PathBuf>,
+    pub cmdline: Vec<String>,
+    pub cwd: Option<PathBuf>,
+    /// Read so detectors can identify games by it, but never serialized, as it can hold tokens
+    /// and other secrets.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub environ: BTreeMap<String, String>,
+    pub start_time: Option<SystemTime>,
+    pub uid: Option<u32>,
+}
+
+impl ProcessInfo {
+    /// Reads everything available about `process`. Fields the caller isn't allowed to read, such as
+    /// the `exe` of another user's process, are left empty.
+    pub fn from_process(process: &Process) -> Self {
+        Self::read(process, procfs::boot_time_secs().ok())
+    }
+
+    // `boot_time` is the btime of the machine `process` runs on, in seconds since the epoch.
+    pub(crate) fn read(process: &Process, boot_time: Option<u64>) -> Self {
+        let stat = process.stat().ok();
+        Self {
+            pid: process.pid,
+            ppid: stat.as_ref().map(|stat| stat.ppid),
+            exe: process.exe().ok(),
+            cmdline: process.cmdline().unwrap_or_default(),
+            cwd: process.cwd().ok(),
+            environ: process.environ()
+                .map(|environ| environ.into_iter()
+                    .map(|(key, value)| (key.to_string_lossy().into_owned(), value.to_string_lossy().into_owned()))
+                    .collect())
+                .unwrap_or_default(),
+            start_time: stat.zip(boot_time).map(|(stat, boot_time)| start_time(&stat, boot_time)),
+            uid: process.uid().ok(),
+        }
+    }
+}
+
+/// The running processes, read once and shared by every detector.
+///
+/// Command lines are joined with spaces and lowercased up front, and processes can be looked up by
+/// PID, by executable name and by the directory their executable lives in.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessSnapshot {
+    processes: Vec<ProcessInfo>,
+    cmdlines: Vec<String>,
+    lowercase_cmdlines: Vec<String>,
+    by_pid: HashMap<i32, usize>,
+    by_exe_name: HashMap<String, Vec<usize>>,
+    by_path: BTreeMap<PathBuf, Vec<usize>>,
+}
+
+/// A process in a [`ProcessSnapshot`] together with its pre-joined command line.
+#[derive(Debug, Clone, Copy)]
+pub struct ProcessEntry<'a> {
+    pub info: &'a ProcessInfo,
+    /// The arguments joined with spaces.
+    pub cmdline: &'a str,
+    /// `cmdline` in lowercase.
+    pub lowercase_cmdline: &'a str,
+}
+
+impl ProcessSnapshot {
+    pub fn new(processes: Vec<ProcessInfo>) -> Self {
+        let mut snapshot = Self::default();
+        for (index, process) in processes.iter().enumerate() {
+            let cmdline = process.cmdline.join(" ");
+            snapshot.lowercase_cmdlines.push(cmdline.to_lowercase());
+            snapshot.cmdlines.push(cmdline);
+            snapshot.by_pid.insert(process.pid, index);
+
+            // Games running under Wine only show up by name in the first argument.
+            let argv0 = process.cmdline.first().map(|argv0| Path::new(argv0.as_str()));
+            for path in process.exe.as_deref().into_iter().chain(argv0) {
+                if let Some(name) = path.to_str().and_then(|path| path.rsplit(['/', '\\']).next()) {
+                    let indices = snapshot.by_exe_name.entry(name.to_owned()).or_default();
+                    if !indices.contains(&index) {
+                        indices.push(index);
+                    }
+                }
+                if path.is_absolute() {
+                    let indices = snapshot.by_path.entry(path.to_path_buf()).or_default();
+                    if !indices.contains(&index) {
+                        indices.push(index);
+                    }
+                }
+            }
+        }
+        snapshot.processes = processes;
+        snapshot
+    }
+
+    pub fn len(&self) -> usize {
+        self.processes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.processes.is_empty()
+    }
+
+    pub fn infos(&self) -> &[ProcessInfo] {
+        &self.processes
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ProcessEntry<'_>> {
+        (0..self.processes.len()).map(|index| self.entry(index))
+    }
+
+    pub fn get(&self, pid: i32) -> Option<ProcessEntry<'_>> {
+        self.by_pid.get(&pid).map(|index| self.entry(*index))
+    }
+
+    /// Processes whose executable, or first argument, has the file name `name`.
+    pub fn by_exe_name(&self, name: &str) -> impl Iterator<Item = ProcessEntry<'_>> {
+        self.by_exe_name.get(name).into_iter().flatten().map(|index| self.entry(*index))
+    }
+
+    /// Processes whose executable, or first argument, is an absolute path below `prefix`.
+    pub fn by_path_prefix(&self, prefix: impl AsRef<Path>) -> Vec<ProcessEntry<'_>> {
+        let prefix = prefix.as_ref();
+        let mut indices: Vec<usize> = self.by_path.range(prefix.to_path_buf()..)
+            .take_while(|(path, _)| path.starts_with(prefix))
+            .flat_map(|(_, indices)| indices.iter().copied())
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+        indices.into_iter().map(|index| self.entry(index)).collect()
+    }
+
+    fn entry(&self, index: usize) -> ProcessEntry<'_> {
+        ProcessEntry {
+            info: &self.processes[index],
+            cmdline: &self.cmdlines[index],
+            lowercase_cmdline: &self.lowercase_cmdlines[index],
+        }
+    }
+}
+
+// `starttime` in /proc/<pid>/stat counts clock ticks since boot.
+fn start_time(stat: &Stat, boot_time: u64) -> SystemTime {
+    let since_boot = Duration::from_secs_f64(stat.starttime as f64 / procfs::ticks_per_second() as f64);
+    SystemTime::UNIX_EPOCH + Duration::from_secs(boot_time) + since_boot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_process() {
+        let info = ProcessInfo::from_process(&Process::myself().unwrap());
+        assert_eq!(info.pid, std::process::id() as i32);
+        assert_eq!(info.ppid, Some(std::os::unix::process::parent_id() as i32));
+        assert_eq!(info.exe, std::env::current_exe().ok());
+        assert_eq!(info.cwd, std::env::current_dir().ok());
+        assert!(info.start_time.unwrap() <= SystemTime::now());
+        assert!(!info.cmdline.is_empty());
+        assert_eq!(info.environ.get("PATH"), std::env::var("PATH").ok().as_ref());
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let process = |pid: i32, exe: &str, cmdline: &[&str]| ProcessInfo {
+            pid,
+            exe: Some(PathBuf::from(exe)),
+            cmdline: cmdline.iter().map(|arg| arg.to_string()).collect(),
+            ..Default::default()
+        };
+        let snapshot = ProcessSnapshot::new(vec![
+            process(1, "/usr/bin/bash", &["bash"]),
+            process(2, "/opt/games/celeste/Celeste", &["/opt/games/celeste/Celeste", "--Fullscreen"]),
+            process(3, "/usr/bin/wine64-preloader", &["Z:\\games\\Hollow Knight\\hollow_knight.exe"]),
+            process(4, "/opt/games-old/game", &["/opt/games-old/game"]),
+        ]);
+
+        assert_eq!(snapshot.len(), 4);
+        let celeste = snapshot.get(2).unwrap();
+        assert_eq!(celeste.cmdline, "/opt/games/celeste/Celeste --Fullscreen");
+        assert_eq!(celeste.lowercase_cmdline, "/opt/games/celeste/celeste --fullscreen");
+        assert!(snapshot.get(5).is_none());
+
+        let pids = |entries: Vec<ProcessEntry>| entries.iter().map(|entry| entry.info.pid).collect::<Vec<_>>();
+        assert_eq!(pids(snapshot.by_exe_name("Celeste").collect()), vec![2]);
+        assert_eq!(pids(snapshot.by_exe_name("hollow_knight.exe").collect()), vec![3]);
+        assert_eq!(pids(snapshot.by_exe_name("wine64-preloader").collect()), vec![3]);
+        assert_eq!(pids(snapshot.by_path_prefix("/opt/games")), vec![2]);
+        assert_eq!(pids(snapshot.by_path_prefix("/usr")), vec![1, 3]);
+    }
+}
@@ -0,0 +1,199 @@
+//! Captures what detection sees into a directory so it can be replayed later, for example in tests.
+//!
+//! A snapshot directory holds `snapshot.json`, with the process list and home directory, and a
+//! `files` directory mirroring every launcher file the detectors read at its original path.
+//!
+//! Unlike a serialized [`ProcessInfo`], `snapshot.json` includes the environment variables the
+//! detectors read, such as `SteamAppId`. Other variables are left out, and the file is only readable
+//! by its owner, but still review a snapshot before sharing it.
+
+use std::collections::BTreeMap;
+use std::fs::{copy, create_dir_all, read_to_string, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::context::relative;
+use crate::{DetectContext, DetectError, DetectorRegistry, ProcessInfo};
+
+const MANIFEST: &str = "snapshot.json";
+const FILES: &str = "files";
+
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    home: Option<PathBuf>,
+    processes: Vec<ProcessInfo>,
+    #[serde(default)]
+    steam_roots: Vec<PathBuf>,
+    // Environments by PID, as `ProcessInfo` leaves them out.
+    #[serde(default)]
+    environ: BTreeMap<i32, BTreeMap<String, String>>,
+}
+
+/// Captures the live system into `dir`.
+pub fn capture(registry: &DetectorRegistry, dir: impl AsRef<Path>) -> Result<(), DetectError> {
+    capture_context(registry, &DetectContext::live()?, dir)
+}
+
+/// Captures `context` and every file `registry` would read from it into `dir`. Launcher files that
+/// don't exist are skipped.
+pub fn capture_context(registry: &DetectorRegistry, context: &DetectContext, dir: impl AsRef<Path>) -> Result<(), DetectError> {
+    let dir = dir.as_ref();
+    let files = dir.join(FILES);
+    create_dir_all(&files).map_err(|e| DetectError::io(&files, e))?;
+
+    for file in registry.data_files(context) {
+        let source = context.path(&file);
+        if !source.is_file() {
+            continue;
+        }
+        let target = files.join(relative(&file));
+        if let Some(parent) = target.parent() {
+            create_dir_all(parent).map_err(|e| DetectError::io(parent, e))?;
+        }
+        copy(&source, &target).map_err(|e| DetectError::io(&source, e))?;
+    }
+
+    let manifest = Manifest {
+        home: context.home().ok().map(Path::to_path_buf),
+        processes: context.processes().infos().to_vec(),
+        steam_roots: steam_roots(context),
+        environ: context.processes().infos().iter()
+            .map(|process| (process.pid, process.environ.iter()
+                .filter(|(key, _)| registry.reads_environ(key))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect::<BTreeMap<_, _>>()))
+            .filter(|(_, environ)| !environ.is_empty())
+            .collect(),
+    };
+    let manifest_path = dir.join(MANIFEST);
+    let manifest = serde_json::to_string_pretty(&manifest).map_err(|e| DetectError::parse(&manifest_path, e))?;
+    write_private(&manifest_path, &manifest).map_err(|e| DetectError::io(&manifest_path, e))
+}
+
+// The installs Steam detection found, which might only be reachable through symlinks that aren't
+// copied.
+fn steam_roots(context: &DetectContext) -> Vec<PathBuf> {
+    #[cfg(feature = "steam")]
+    if let Ok(roots) = crate::platforms::steam::steam_roots(context) {
+        return roots;
+    }
+    context.steam_roots().to_vec()
+}
+
+// Created readable by the owner only, and made so if it already existed.
+fn write_private(path: &Path, contents: &str) -> io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path)?;
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    file.write_all(contents.as_bytes())
+}
+
+/// Loads a snapshot captured with [`capture`] as a context detectors can run against.
+pub fn load(dir: impl AsRef<Path>) -> Result<DetectContext, DetectError> {
+    let dir = dir.as_ref();
+    let manifest_path = dir.join(MANIFEST);
+    let manifest = read_to_string(&manifest_path).map_err(|e| DetectError::io(&manifest_path, e))?;
+    let mut manifest: Manifest = serde_json::from_str(&manifest).map_err(|e| DetectError::parse(&manifest_path, e))?;
+    for process in &mut manifest.processes {
+        process.environ = manifest.environ.remove(&process.pid).unwrap_or_default();
+    }
+    let context = DetectContext::new(manifest.processes, manifest.home);
+    let context = context.with_steam_roots(manifest.steam_roots);
+    Ok(context.with_root(dir.join(FILES)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+    use crate::{DetectedGame, Detection, Detector};
+
+    // Reports a custom game for every process whose marker file contains its PID.
+    struct Marker;
+
+    impl Detector for Marker {
+        fn name(&self) -> &str {
+            "marker"
+        }
+
+        fn detect(&self, context: &DetectContext) -> Result<Vec<Detection>, DetectError> {
+            let marker = read_to_string(context.path(context.home()?.join("marker"))).unwrap_or_default();
+            Ok(context.processes().iter()
+                .filter(|process| marker.trim() == process.info.pid.to_string())
+                .map(|process| Detection::new(DetectedGame::custom(process.cmdline), Some(process.info.clone())))
+                .collect())
+        }
+
+        fn data_files(&self, context: &DetectContext) -> Vec<PathBuf> {
+            context.home().map(|home| vec![home.join("marker"), home.join("missing")]).unwrap_or_default()
+        }
+
+        fn reads_environ(&self, key: &str) -> bool {
+            key == "SteamAppId"
+        }
+    }
+
+    #[test]
+    fn test_capture_and_load() {
+        let dir = TempDir::new("snapshot");
+        let home = dir.join("home");
+        create_dir_all(&home).unwrap();
+        std::fs::write(home.join("marker"), "42").unwrap();
+
+        let mut registry = DetectorRegistry::empty();
+        registry.register(Marker);
+        let process = ProcessInfo {
+            pid: 42,
+            cmdline: vec!["game".to_owned()],
+            environ: BTreeMap::from([("SteamAppId".to_owned(), "620".to_owned()), ("API_TOKEN".to_owned(), "secret".to_owned())]),
+            ..Default::default()
+        };
+        let context = DetectContext::new(vec![process.clone()], Some(home.clone()));
+        let expected = registry.detect_all_with(&context).unwrap();
+        assert_eq!(expected, vec![Detection::new(DetectedGame::custom("game"), Some(process.clone()))]);
+
+        let snapshot = dir.join("snapshot");
+        capture_context(&registry, &context, &snapshot).unwrap();
+        std::fs::remove_file(home.join("marker")).unwrap();
+
+        let replayed = load(&snapshot).unwrap();
+        let mut process = process;
+        process.environ.remove("API_TOKEN");
+        assert_eq!(registry.detect_all_with(&replayed).unwrap(), vec![Detection::new(DetectedGame::custom("game"), Some(process))]);
+        #[cfg(unix)]
+        assert_eq!(std::os::unix::fs::PermissionsExt::mode(&std::fs::metadata(snapshot.join(MANIFEST)).unwrap().permissions()) & 0o777, 0o600);
+    }
+
+    #[test]
+    #[cfg(all(feature = "steam", target_os = "linux"))]
+    fn test_symlinked_steam_root() {
+        use crate::platforms::steam::SteamDetector;
+        use crate::testing::launched;
+
+        let dir = TempDir::new("snapshot-steam");
+        let home = dir.join("home");
+        let root = home.join(".steam/debian-installation");
+        let grid = root.join("userdata/42/config/grid");
+        create_dir_all(&grid).unwrap();
+        std::fs::write(grid.join("620_icon.png"), "").unwrap();
+        std::os::unix::fs::symlink("debian-installation", home.join(".steam/root")).unwrap();
+
+        let mut registry = DetectorRegistry::empty();
+        registry.register(SteamDetector::new());
+        let context = DetectContext::new(vec![launched(10, 1, &["/games/portal2/portal2_linux"], &[("SteamAppId", "620")])], Some(home.clone()));
+        let expected = registry.detect_all_with(&context).unwrap();
+        assert!(matches!(&expected[0].game, DetectedGame::Steam { icon, .. } if icon.starts_with(&*root.to_string_lossy())));
+
+        capture_context(&registry, &context, dir.join("snapshot")).unwrap();
+        std::fs::remove_dir_all(&home).unwrap();
+        let replayed = load(dir.join("snapshot")).unwrap();
+        assert_eq!(replayed.steam_roots(), [root]);
+        assert_eq!(format!("{:?}", registry.detect_all_with(&replayed).unwrap()), format!("{expected:?}"));
+    }
+}
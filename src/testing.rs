@@ -0,0 +1,71 @@
+//! Fixtures shared by the unit tests. Not every one is used with every set of features.
+#![allow(dead_code)]
+
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{DetectedGame, Detection, ProcessInfo};
+
+/// A process started by `ppid` that runs `exe` with `cmdline`.
+pub(crate) fn process(pid: i32, ppid: i32, exe: &str, cmdline: &[&str]) -> ProcessInfo {
+    ProcessInfo {
+        pid,
+        ppid: Some(ppid),
+        exe: Some(PathBuf::from(exe)),
+        cmdline: cmdline.iter().map(|arg| arg.to_string()).collect(),
+        ..Default::default()
+    }
+}
+
+/// A process launched with `environ` that runs the first argument of `cmdline`.
+pub(crate) fn launched(pid: i32, ppid: i32, cmdline: &[&str], environ: &[(&str, &str)]) -> ProcessInfo {
+    ProcessInfo {
+        exe: cmdline.first().map(PathBuf::from),
+        environ: environ.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect(),
+        ..process(pid, ppid, "", cmdline)
+    }
+}
+
+/// A custom game detected in `pid`, or without a process.
+pub(crate) fn custom(id: &str, pid: Option<i32>) -> Detection {
+    Detection::new(DetectedGame::custom(id.to_owned()), pid.map(|pid| ProcessInfo { pid, ..Default::default() }))
+}
+
+/// A directory in the system's temporary directory, named after the test, this process and a
+/// counter so tests running in parallel never share one. It is removed when dropped so failing tests
+/// don't leave it behind.
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+    pub(crate) fn new(name: &str) -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!("game-detect-{name}-{}-{count}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Creates a native Steam install with an empty library in `home` and returns its root.
+#[cfg(all(feature = "steam", target_os = "linux"))]
+pub(crate) fn create_steam_root(home: &Path) -> PathBuf {
+    let root = home.join(".local/share/Steam");
+    std::fs::create_dir_all(root.join("steamapps")).unwrap();
+    root
+}
@@ -0,0 +1,276 @@
+use std::collections::{HashMap, VecDeque};
+#[cfg(feature = "stream")]
+use std::{pin::Pin, task::{Context, Poll}};
+use std::thread::sleep;
+use std::time::{Duration, SystemTime};
+
+use crate::{DetectContext, DetectError, DetectOptions, DetectedGame, Detection, DetectorRegistry};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameEvent {
+    GameStarted {
+        game: DetectedGame,
+        pid: Option<i32>,
+        at: SystemTime,
+    },
+    GameStopped {
+        game: DetectedGame,
+        pid: Option<i32>,
+        started_at: SystemTime,
+        at: SystemTime,
+    },
+    /// The same process (for example a Roblox client joining another place) now runs a different game.
+    GameChanged {
+        from: DetectedGame,
+        to: DetectedGame,
+        pid: Option<i32>,
+        started_at: SystemTime,
+        at: SystemTime,
+    },
+}
+
+// Detections with a PID are tracked per process, so a process switching games is reported as a change.
+// Detections without one can only be told apart by the game itself.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum SessionKey {
+    Process(i32),
+    Game(DetectedGame),
+}
+
+struct Session {
+    detection: Detection,
+    // The detector that reported it, when known.
+    detector: Option<String>,
+    started_at: SystemTime,
+}
+
+/// Polls a [`DetectorRegistry`] and reports games starting, stopping and changing.
+///
+/// The watcher is a blocking iterator that sleeps for `interval` between polls and never ends. Use
+/// [`GameWatcher::poll`] to drive it manually, or `into_stream` for an async `Stream`.
+pub struct GameWatcher {
+    registry: DetectorRegistry,
+    options: DetectOptions,
+    interval: Duration,
+    sessions: HashMap<SessionKey, Session>,
+    pending: VecDeque<Result<GameEvent, DetectError>>,
+    polled: bool,
+}
+
+impl GameWatcher {
+    pub fn new(registry: DetectorRegistry, interval: Duration) -> Self {
+        Self {
+            registry,
+            options: DetectOptions::default(),
+            interval,
+            sessions: HashMap::new(),
+            pending: VecDeque::new(),
+            polled: false,
+        }
+    }
+
+    /// Reads processes and launcher data from where `options` points on every poll.
+    pub fn with_options(mut self, options: DetectOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn running(&self) -> Vec<Detection> {
+        self.sessions.values().map(|session| session.detection.clone()).collect()
+    }
+
+    /// Runs detection once and returns the events since the previous poll, followed by a
+    /// [`DetectError::Detector`] for each detector that failed. The games a failed detector saw
+    /// last are assumed to still run until it succeeds again, as its launcher data might just be
+    /// in the middle of being written.
+    pub fn poll(&mut self) -> Vec<Result<GameEvent, DetectError>> {
+        let context = match DetectContext::with_options(&self.options) {
+            Ok(context) => context,
+            Err(error) => return vec![Err(error)],
+        };
+        let report = self.registry.detect_all_report(&context);
+        let mut detections: Vec<(Detection, Option<String>)> = report.detections.into_iter()
+            .zip(report.detectors.into_iter().map(Some))
+            .collect();
+        detections.extend(self.sessions.values()
+            .filter(|session| report.errors.iter().any(|(name, _)| session.detector.as_ref() == Some(name)))
+            .map(|session| (session.detection.clone(), session.detector.clone())));
+
+        let mut results: Vec<Result<GameEvent, DetectError>> = self.diff(detections, SystemTime::now()).into_iter().map(Ok).collect();
+        results.extend(report.errors.into_iter().map(|(name, error)| Err(DetectError::Detector { name, source: Box::new(error) })));
+        results
+    }
+
+    /// Diffs `detections` against the games seen so far, as if they were detected at `at`.
+    pub fn update(&mut self, detections: Vec<Detection>, at: SystemTime) -> Vec<GameEvent> {
+        self.diff(detections.into_iter().map(|detection| (detection, None)).collect(), at)
+    }
+
+    fn diff(&mut self, detections: Vec<(Detection, Option<String>)>, at: SystemTime) -> Vec<GameEvent> {
+        let mut events = vec![];
+        let mut sessions = HashMap::new();
+        for (detection, detector) in detections {
+            let pid = detection.pid();
+            let key = match pid {
+                Some(pid) => SessionKey::Process(pid),
+                None => SessionKey::Game(detection.game.clone()),
+            };
+            if sessions.contains_key(&key) {
+                continue;
+            }
+
+            let session = match self.sessions.remove(&key) {
+                Some(previous) if previous.detection.game == detection.game => Session { detector, ..previous },
+                Some(previous) => {
+                    events.push(GameEvent::GameChanged {
+                        from: previous.detection.game,
+                        to: detection.game.clone(),
+                        pid,
+                        started_at: previous.started_at,
+                        at,
+                    });
+                    Session { detection, detector, started_at: at }
+                }
+                None => {
+                    events.push(GameEvent::GameStarted { game: detection.game.clone(), pid, at });
+                    Session { detection, detector, started_at: at }
+                }
+            };
+            sessions.insert(key, session);
+        }
+
+        for (_key, session) in self.sessions.drain() {
+            events.push(GameEvent::GameStopped {
+                pid: session.detection.pid(),
+                game: session.detection.game,
+                started_at: session.started_at,
+                at,
+            });
+        }
+        self.sessions = sessions;
+        events
+    }
+
+    /// Moves the watcher onto a background thread and returns its events as an async `Stream`.
+    /// The thread stops once the stream is dropped.
+    #[cfg(feature = "stream")]
+    pub fn into_stream(mut self) -> GameEventStream {
+        let (sender, receiver) = futures_channel::mpsc::unbounded();
+        std::thread::spawn(move || {
+            while !sender.is_closed() {
+                for item in self.poll() {
+                    if sender.unbounded_send(item).is_err() {
+                        return;
+                    }
+                }
+                sleep(self.interval);
+            }
+        });
+        GameEventStream { receiver }
+    }
+}
+
+impl Iterator for GameWatcher {
+    type Item = Result<GameEvent, DetectError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.pending.pop_front() {
+                return Some(item);
+            }
+            if self.polled {
+                sleep(self.interval);
+            }
+            self.polled = true;
+            let items = self.poll();
+            self.pending.extend(items);
+        }
+    }
+}
+
+#[cfg(feature = "stream")]
+pub struct GameEventStream {
+    receiver: futures_channel::mpsc::UnboundedReceiver<Result<GameEvent, DetectError>>,
+}
+
+#[cfg(feature = "stream")]
+impl futures_core::Stream for GameEventStream {
+    type Item = Result<GameEvent, DetectError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::custom;
+    use crate::Detector;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // Sees game "a", then fails as if its launcher data were half written, then sees nothing.
+    #[derive(Default)]
+    struct Flaky(AtomicUsize);
+
+    impl Detector for Flaky {
+        fn name(&self) -> &str {
+            "flaky"
+        }
+
+        fn detect(&self, _context: &DetectContext) -> Result<Vec<Detection>, DetectError> {
+            match self.0.fetch_add(1, Ordering::SeqCst) {
+                0 => Ok(vec![custom("a", None)]),
+                1 => Err(DetectError::parse("packages.wttf", "unexpected end of file")),
+                _ => Ok(vec![]),
+            }
+        }
+    }
+
+    #[test]
+    fn test_update() {
+        let mut watcher = GameWatcher::new(DetectorRegistry::empty(), Duration::from_secs(1));
+        let t0 = SystemTime::UNIX_EPOCH;
+        let t1 = t0 + Duration::from_secs(5);
+        let t2 = t1 + Duration::from_secs(5);
+
+        let events = watcher.update(vec![custom("a", Some(1)), custom("b", None)], t0);
+        assert_eq!(events.len(), 2);
+        assert!(events.contains(&GameEvent::GameStarted { game: DetectedGame::custom("a"), pid: Some(1), at: t0 }));
+        assert!(events.contains(&GameEvent::GameStarted { game: DetectedGame::custom("b"), pid: None, at: t0 }));
+
+        assert!(watcher.update(vec![custom("a", Some(1)), custom("b", None)], t1).is_empty());
+
+        let events = watcher.update(vec![custom("c", Some(1))], t2);
+        assert_eq!(events.len(), 2);
+        assert!(events.contains(&GameEvent::GameChanged {
+            from: DetectedGame::custom("a"),
+            to: DetectedGame::custom("c"),
+            pid: Some(1),
+            started_at: t0,
+            at: t2,
+        }));
+        assert!(events.contains(&GameEvent::GameStopped { game: DetectedGame::custom("b"), pid: None, started_at: t0, at: t2 }));
+        assert_eq!(watcher.running(), vec![custom("c", Some(1))]);
+    }
+
+    #[test]
+    fn test_failing_detector() {
+        let mut registry = DetectorRegistry::empty();
+        registry.register(Flaky::default());
+        let mut watcher = GameWatcher::new(registry, Duration::from_secs(1));
+
+        let events = watcher.poll();
+        let started_at = match events.as_slice() {
+            [Ok(GameEvent::GameStarted { game, at, .. })] if *game == DetectedGame::custom("a") => *at,
+            _ => panic!("unexpected events {events:?}"),
+        };
+
+        let events = watcher.poll();
+        assert!(matches!(events.as_slice(), [Err(DetectError::Detector { name, .. })] if name == "flaky"), "unexpected events {events:?}");
+        assert_eq!(watcher.running(), vec![custom("a", None)]);
+
+        let events = watcher.poll();
+        assert!(matches!(events.as_slice(), [Ok(GameEvent::GameStopped { started_at: at, .. })] if *at == started_at), "unexpected events {events:?}");
+    }
+}